@@ -186,7 +186,7 @@ impl Prompt for Byop {
         self.task_monitor.abort_all();
 
         // Reset the text editor state for the next prompt.
-        self.readline.texteditor.erase_all();
+        self.readline.texteditor = Default::default();
 
         Ok(ret)
     }
@@ -369,7 +369,7 @@ impl Byop {
                 modifiers: KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 state: KeyEventState::NONE,
-            }) => self.readline.texteditor.erase_all(),
+            }) => self.readline.kill_all(),
 
             // Erase to the nearest character.
             Event::Key(KeyEvent {
@@ -377,20 +377,14 @@ impl Byop {
                 modifiers: KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 state: KeyEventState::NONE,
-            }) => self
-                .readline
-                .texteditor
-                .erase_to_previous_nearest(&self.readline.config.word_break_chars),
+            }) => self.readline.kill_to_previous_nearest(),
 
             Event::Key(KeyEvent {
                 code: KeyCode::Char('d'),
                 modifiers: KeyModifiers::ALT,
                 kind: KeyEventKind::Press,
                 state: KeyEventState::NONE,
-            }) => self
-                .readline
-                .texteditor
-                .erase_to_next_nearest(&self.readline.config.word_break_chars),
+            }) => self.readline.kill_to_next_nearest(),
 
             // Input char.
             Event::Key(KeyEvent {
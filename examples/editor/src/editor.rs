@@ -0,0 +1,13 @@
+use promkit::{preset::editor::Editor, Prompt};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let ret = Editor::default()
+        .title("Commit message (Alt+Enter or Ctrl+D to submit)")
+        .line_numbers(true)
+        .lines(10)
+        .run()
+        .await?;
+    println!("result: {:?}", ret);
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use crate::crossterm::{cursor, terminal};
+
+mod memory;
+pub use memory::Memory;
+
+/// An output destination that a prompt draws on.
+///
+/// Besides writing the escape sequences, a backend answers the terminal size
+/// and cursor position queries, and toggles raw mode.
+/// Implementations only need to be `Clone` to be shared between
+/// a prompt and its renderer.
+pub trait Backend: Write + Send + Sync + BackendClone {
+    /// Returns the size of the terminal as `(columns, rows)`.
+    fn size(&self) -> anyhow::Result<(u16, u16)>;
+
+    /// Returns the current cursor position as `(column, row)`.
+    fn position(&mut self) -> anyhow::Result<(u16, u16)>;
+
+    /// Enables raw mode for the terminal.
+    fn enable_raw_mode(&mut self) -> anyhow::Result<()> {
+        terminal::enable_raw_mode()?;
+        Ok(())
+    }
+
+    /// Disables raw mode for the terminal.
+    fn disable_raw_mode(&mut self) -> anyhow::Result<()> {
+        terminal::disable_raw_mode()?;
+        Ok(())
+    }
+}
+
+/// Allows cloning a boxed [`Backend`].
+pub trait BackendClone {
+    fn clone_box(&self) -> Box<dyn Backend>;
+}
+
+impl<T: Backend + Clone + 'static> BackendClone for T {
+    fn clone_box(&self) -> Box<dyn Backend> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Backend> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Draws on the standard output.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stdout;
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl Backend for Stdout {
+    fn size(&self) -> anyhow::Result<(u16, u16)> {
+        Ok(terminal::size()?)
+    }
+
+    fn position(&mut self) -> anyhow::Result<(u16, u16)> {
+        Ok(cursor::position()?)
+    }
+}
+
+/// Draws on the standard error,
+/// leaving the standard output free for the result (e.g. `mytool | jq`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stderr;
+
+impl Write for Stderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+impl Backend for Stderr {
+    fn size(&self) -> anyhow::Result<(u16, u16)> {
+        Ok(terminal::size()?)
+    }
+
+    fn position(&mut self) -> anyhow::Result<(u16, u16)> {
+        query_position(self)
+    }
+}
+
+/// Draws directly on the controlling terminal
+/// (`/dev/tty` on Unix, `CONOUT$` on Windows),
+/// regardless of where the standard streams are redirected.
+#[derive(Clone, Debug)]
+pub struct Tty(Arc<std::fs::File>);
+
+impl Tty {
+    /// Opens the controlling terminal for writing.
+    pub fn try_new() -> anyhow::Result<Self> {
+        #[cfg(unix)]
+        let path = "/dev/tty";
+        #[cfg(windows)]
+        let path = "CONOUT$";
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        Ok(Self(Arc::new(file)))
+    }
+}
+
+impl Write for Tty {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self.0).flush()
+    }
+}
+
+impl Backend for Tty {
+    fn size(&self) -> anyhow::Result<(u16, u16)> {
+        Ok(terminal::size()?)
+    }
+
+    fn position(&mut self) -> anyhow::Result<(u16, u16)> {
+        query_position(self)
+    }
+}
+
+/// Queries the cursor position through `crossterm::cursor::position`,
+/// which reads the report with the event reader shared by the prompts,
+/// keeping the keys typed meanwhile as events.
+///
+/// The query is always sent to stdout, which would end up in the pipe
+/// when stdout is redirected. In that case stdout is pointed at
+/// the controlling terminal while querying.
+#[cfg(unix)]
+fn query_position<W: Write>(writer: &mut W) -> anyhow::Result<(u16, u16)> {
+    use std::os::fd::AsRawFd;
+
+    writer.flush()?;
+    // Keep other threads from writing to stdout while it is pointed at the terminal.
+    let mut stdout = io::stdout().lock();
+    if unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1 {
+        return Ok(cursor::position()?);
+    }
+    stdout.flush()?;
+
+    let tty = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if saved < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    if unsafe { libc::dup2(tty.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        let e = io::Error::last_os_error();
+        unsafe { libc::close(saved) };
+        return Err(e.into());
+    }
+    let ret = cursor::position();
+    let restored = unsafe { libc::dup2(saved, libc::STDOUT_FILENO) };
+    unsafe { libc::close(saved) };
+    if restored < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(ret?)
+}
+
+#[cfg(windows)]
+fn query_position<W: Write>(_writer: &mut W) -> anyhow::Result<(u16, u16)> {
+    // On Windows, the position is read through the console API.
+    Ok(cursor::position()?)
+}
@@ -0,0 +1,324 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use unicode_width::UnicodeWidthChar;
+
+use super::Backend;
+
+/// Records the output in memory instead of drawing on a terminal.
+///
+/// The size and the initial cursor position are fixed,
+/// and raw mode is never touched, so it works without a terminal (e.g. in tests).
+/// [`Memory::lines`] replays the recorded escape sequences on a virtual screen
+/// to inspect the rendered frame.
+#[derive(Clone, Debug)]
+pub struct Memory {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    size: (u16, u16),
+    position: (u16, u16),
+}
+
+impl Memory {
+    /// Creates an empty in-memory backend with the given size.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            buffer: Default::default(),
+            size: (width, height),
+            position: (0, 0),
+        }
+    }
+
+    /// Sets the cursor position reported by the backend.
+    pub fn cursor_position(mut self, column: u16, row: u16) -> Self {
+        self.position = (column, row);
+        self
+    }
+
+    /// Returns the raw bytes written so far.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.buffer.lock().unwrap().clone()
+    }
+
+    /// Returns the output written so far as a (lossy) UTF-8 string.
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(&self.bytes()).into_owned()
+    }
+
+    /// Returns the rows of the virtual screen after replaying the output,
+    /// with trailing whitespace removed. Styles are ignored.
+    pub fn lines(&self) -> Vec<String> {
+        let mut screen = Screen::new(self.size.0 as usize, self.size.1 as usize);
+        screen.feed(&self.output());
+        screen.lines()
+    }
+}
+
+impl Write for Memory {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Backend for Memory {
+    fn size(&self) -> anyhow::Result<(u16, u16)> {
+        Ok(self.size)
+    }
+
+    fn position(&mut self) -> anyhow::Result<(u16, u16)> {
+        Ok(self.position)
+    }
+
+    fn enable_raw_mode(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// A minimal terminal emulator covering the sequences emitted by crossterm commands
+/// used in promkit: printing with auto-wrap, cursor movement, erasing and scrolling.
+struct Screen {
+    width: usize,
+    height: usize,
+    /// Each cell holds the text printed there;
+    /// the cell right after a wide character is left empty.
+    cells: Vec<Vec<String>>,
+    x: usize,
+    y: usize,
+    /// Set after printing into the last column; the next character wraps first.
+    pending_wrap: bool,
+    /// The last printed cell, where zero-width characters are appended.
+    last: Option<(usize, usize)>,
+}
+
+impl Screen {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Self::blank_row(width); height],
+            x: 0,
+            y: 0,
+            pending_wrap: false,
+            last: None,
+        }
+    }
+
+    fn blank_row(width: usize) -> Vec<String> {
+        vec![String::from(" "); width]
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| row.concat().trim_end().to_string())
+            .collect()
+    }
+
+    fn feed(&mut self, output: &str) {
+        let mut chars = output.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\x1B' => {
+                    if chars.next() == Some('[') {
+                        let mut params = String::new();
+                        for ch in chars.by_ref() {
+                            if ('\x40'..='\x7E').contains(&ch) {
+                                self.csi(&params, ch);
+                                break;
+                            }
+                            params.push(ch);
+                        }
+                    }
+                }
+                '\r' => self.move_to(0, self.y),
+                '\n' => self.line_feed(),
+                ch if ch.is_control() => {}
+                ch => self.print(ch),
+            }
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        // Private modes (e.g. cursor visibility, mouse capture) do not affect the screen.
+        if params.starts_with('?') {
+            return;
+        }
+        let args = params
+            .split(';')
+            .map(|arg| arg.parse::<usize>().unwrap_or(0))
+            .collect::<Vec<_>>();
+        let arg = |i: usize, default: usize| match args.get(i) {
+            Some(0) | None => default,
+            Some(v) => *v,
+        };
+
+        match action {
+            'H' | 'f' => self.move_to(arg(1, 1) - 1, arg(0, 1) - 1),
+            'A' => self.move_to(self.x, self.y.saturating_sub(arg(0, 1))),
+            'B' => self.move_to(self.x, self.y + arg(0, 1)),
+            'C' => self.move_to(self.x + arg(0, 1), self.y),
+            'D' => self.move_to(self.x.saturating_sub(arg(0, 1)), self.y),
+            'E' => self.move_to(0, self.y + arg(0, 1)),
+            'F' => self.move_to(0, self.y.saturating_sub(arg(0, 1))),
+            'G' => self.move_to(arg(0, 1) - 1, self.y),
+            'J' => match args.first().copied().unwrap_or(0) {
+                0 => {
+                    self.erase_line(self.x, self.width);
+                    for row in self.cells.iter_mut().skip(self.y + 1) {
+                        *row = Self::blank_row(self.width);
+                    }
+                }
+                1 => {
+                    self.erase_line(0, self.x + 1);
+                    for row in self.cells.iter_mut().take(self.y) {
+                        *row = Self::blank_row(self.width);
+                    }
+                }
+                _ => self.cells = vec![Self::blank_row(self.width); self.height],
+            },
+            'K' => match args.first().copied().unwrap_or(0) {
+                0 => self.erase_line(self.x, self.width),
+                1 => self.erase_line(0, self.x + 1),
+                _ => self.erase_line(0, self.width),
+            },
+            'S' => self.scroll_up(arg(0, 1)),
+            'T' => {
+                for _ in 0..arg(0, 1).min(self.height) {
+                    self.cells.pop();
+                    self.cells.insert(0, Self::blank_row(self.width));
+                }
+            }
+            // Styles (`m`) and the remaining sequences do not change the text.
+            _ => {}
+        }
+    }
+
+    fn move_to(&mut self, x: usize, y: usize) {
+        self.x = x.min(self.width.saturating_sub(1));
+        self.y = y.min(self.height.saturating_sub(1));
+        self.pending_wrap = false;
+    }
+
+    fn line_feed(&mut self) {
+        if self.y + 1 >= self.height {
+            self.scroll_up(1);
+        } else {
+            self.y += 1;
+        }
+        self.pending_wrap = false;
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        for _ in 0..n.min(self.height) {
+            self.cells.remove(0);
+            self.cells.push(Self::blank_row(self.width));
+        }
+        self.last = None;
+    }
+
+    fn erase_line(&mut self, from: usize, to: usize) {
+        if let Some(row) = self.cells.get_mut(self.y) {
+            for cell in row.iter_mut().take(to).skip(from) {
+                *cell = String::from(" ");
+            }
+        }
+        self.pending_wrap = false;
+    }
+
+    fn print(&mut self, ch: char) {
+        let width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if width == 0 {
+            if let Some((x, y)) = self.last {
+                self.cells[y][x].push(ch);
+            }
+            return;
+        }
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+
+        if self.pending_wrap || self.x + width > self.width {
+            self.x = 0;
+            self.line_feed();
+        }
+
+        self.cells[self.y][self.x] = ch.to_string();
+        if width == 2 && self.x + 1 < self.width {
+            self.cells[self.y][self.x + 1] = String::new();
+        }
+        self.last = Some((self.x, self.y));
+
+        if self.x + width >= self.width {
+            self.x = self.width - 1;
+            self.pending_wrap = true;
+        } else {
+            self.x += width;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod lines {
+        use super::*;
+
+        use crate::crossterm::{cursor, queue, style, terminal};
+
+        #[test]
+        fn test_shared_between_clones() {
+            let memory = Memory::new(10, 2);
+            let mut backend: Box<dyn Backend> = Box::new(memory.clone());
+            write!(backend, "abc").unwrap();
+            assert_eq!("abc", memory.output());
+            assert_eq!(vec!["abc", ""], memory.lines());
+        }
+
+        #[test]
+        fn test_wrap_and_scroll() {
+            let mut memory = Memory::new(3, 2);
+            write!(memory, "abcdefg").unwrap();
+            assert_eq!(vec!["def", "g"], memory.lines());
+        }
+
+        #[test]
+        fn test_move_and_clear() {
+            let mut memory = Memory::new(10, 3);
+            write!(memory, "hello\r\nworld").unwrap();
+            queue!(
+                memory,
+                cursor::MoveTo(2, 0),
+                terminal::Clear(terminal::ClearType::UntilNewLine),
+                style::Print("y!"),
+                cursor::MoveTo(0, 1),
+                terminal::Clear(terminal::ClearType::FromCursorDown),
+            )
+            .unwrap();
+            assert_eq!(vec!["hey!", "", ""], memory.lines());
+        }
+
+        #[test]
+        fn test_with_styles_and_wide_chars() {
+            let mut memory = Memory::new(10, 1);
+            queue!(
+                memory,
+                cursor::Hide,
+                style::PrintStyledContent(style::Stylize::red("あい")),
+                style::Print("e\u{301}"),
+            )
+            .unwrap();
+            assert_eq!(vec!["あいe\u{301}"], memory.lines());
+        }
+    }
+}
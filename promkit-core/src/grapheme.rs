@@ -1,46 +1,52 @@
 use std::{collections::VecDeque, fmt};
 
 use crossterm::style::{Attribute, ContentStyle};
-use unicode_width::UnicodeWidthChar;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-/// Represents a single grapheme (character) with its display width and optional styling.
+/// Represents a single extended grapheme cluster (UAX #29)
+/// with its display width and optional styling.
 ///
-/// This structure is similar to `Grapheme` but includes styling information directly.
+/// A cluster may consist of several chars, e.g. a base character followed by
+/// combining marks, an emoji ZWJ sequence, or a pair of regional indicators.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StyledGrapheme {
-    ch: char,
+    cluster: String,
     width: usize,
     style: ContentStyle,
 }
 
 impl From<char> for StyledGrapheme {
     fn from(ch: char) -> Self {
-        Self {
-            ch,
-            width: UnicodeWidthChar::width(ch).unwrap_or(0),
-            style: ContentStyle::default(),
-        }
+        Self::new(ch, ContentStyle::default())
     }
 }
 
 impl fmt::Display for StyledGraphemes {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for styled_grapheme in self.iter() {
-            write!(f, "{}", styled_grapheme.ch)?;
+            write!(f, "{}", styled_grapheme.cluster)?;
         }
         Ok(())
     }
 }
 
 impl StyledGrapheme {
-    pub fn new(ch: char, style: ContentStyle) -> Self {
+    /// Creates a styled grapheme from a char or a single grapheme cluster.
+    pub fn new<S: Into<String>>(cluster: S, style: ContentStyle) -> Self {
+        let cluster = cluster.into();
         Self {
-            ch,
-            width: UnicodeWidthChar::width(ch).unwrap_or(0),
+            width: cluster_width(&cluster),
+            cluster,
             style,
         }
     }
 
+    /// Returns the grapheme cluster as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.cluster
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -48,6 +54,30 @@ impl StyledGrapheme {
     pub fn apply_style(&mut self, style: ContentStyle) {
         self.style = style;
     }
+
+    /// Appends `ch` to this grapheme if the result is still a single grapheme cluster
+    /// (e.g. a combining mark or a ZWJ-joined emoji), and returns whether it was appended.
+    pub fn extend(&mut self, ch: char) -> bool {
+        let mut cluster = self.cluster.clone();
+        cluster.push(ch);
+        if cluster.graphemes(true).count() != 1 {
+            return false;
+        }
+        self.width = cluster_width(&cluster);
+        self.cluster = cluster;
+        true
+    }
+}
+
+/// Calculates the display width of a grapheme cluster.
+///
+/// A lone char keeps the char-level width so that control characters stay zero-width.
+fn cluster_width(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => UnicodeWidthChar::width(ch).unwrap_or(0),
+        _ => UnicodeWidthStr::width(cluster),
+    }
 }
 
 /// A collection of `StyledGrapheme` instances.
@@ -93,10 +123,18 @@ impl<S: AsRef<str>> From<S> for StyledGraphemes {
     }
 }
 
+/// Renders the graphemes as they are, e.g. to place precomputed rows in a
+/// [`Layout`](crate::layout::Layout).
+impl crate::Widget for StyledGraphemes {
+    fn create_graphemes(&self, _width: u16, _height: u16) -> StyledGraphemes {
+        self.clone()
+    }
+}
+
 impl fmt::Debug for StyledGraphemes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for styled_grapheme in self.iter() {
-            write!(f, "{}", styled_grapheme.ch)?;
+            write!(f, "{}", styled_grapheme.cluster)?;
         }
         Ok(())
     }
@@ -107,8 +145,8 @@ impl StyledGraphemes {
     pub fn from_str<S: AsRef<str>>(string: S, style: ContentStyle) -> Self {
         string
             .as_ref()
-            .chars()
-            .map(|ch| StyledGrapheme::new(ch, style))
+            .graphemes(true)
+            .map(|cluster| StyledGrapheme::new(cluster, style))
             .collect()
     }
 
@@ -131,7 +169,7 @@ impl StyledGraphemes {
         merged
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &StyledGrapheme> {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &StyledGrapheme> + ExactSizeIterator {
         self.0.iter()
     }
 
@@ -145,7 +183,10 @@ impl StyledGraphemes {
 
     /// Returns a `Vec<char>` containing the characters of all `Grapheme` instances in the collection.
     pub fn chars(&self) -> Vec<char> {
-        self.0.iter().map(|grapheme| grapheme.ch).collect()
+        self.0
+            .iter()
+            .flat_map(|grapheme| grapheme.cluster.chars())
+            .collect()
     }
 
     /// Calculates the total display width of all `Grapheme` instances in the collection.
@@ -199,6 +240,24 @@ impl StyledGraphemes {
         self
     }
 
+    /// Applies a given style beneath the styles of the graphemes,
+    /// so that the colors and attributes already set on a grapheme
+    /// (e.g. a highlighted match) take precedence over `style`.
+    pub fn apply_base_style(mut self, style: ContentStyle) -> Self {
+        for grapheme in &mut self.0 {
+            let own = grapheme.style;
+            let mut attributes = style.attributes;
+            attributes.extend(own.attributes);
+            grapheme.style = ContentStyle {
+                foreground_color: own.foreground_color.or(style.foreground_color),
+                background_color: own.background_color.or(style.background_color),
+                underline_color: own.underline_color.or(style.underline_color),
+                attributes,
+            };
+        }
+        self
+    }
+
     /// Applies a given style to a specific `StyledGrapheme` at the specified index.
     pub fn apply_style_at(mut self, idx: usize, style: ContentStyle) -> Self {
         if let Some(grapheme) = self.0.get_mut(idx) {
@@ -224,14 +283,14 @@ impl StyledGraphemes {
 
         let mut indices = Vec::new();
         let mut pos = 0;
-        let query_chars: Vec<char> = query_str.chars().collect();
-        let query_len = query_chars.len();
+        let query_clusters: Vec<&str> = query_str.graphemes(true).collect();
+        let query_len = query_clusters.len();
 
         // Iterate through each grapheme in self
         while pos + query_len <= self.0.len() {
             let mut match_found = true;
-            for (i, query_char) in query_chars.iter().enumerate() {
-                if self.0[pos + i].ch != *query_char {
+            for (i, query_cluster) in query_clusters.iter().enumerate() {
+                if self.0[pos + i].cluster != *query_cluster {
                     match_found = false;
                     break;
                 }
@@ -267,7 +326,7 @@ impl StyledGraphemes {
             return None;
         }
 
-        let query_len = query_str.chars().count();
+        let query_len = query_str.graphemes(true).count();
 
         for &start_index in &indices {
             for i in start_index..start_index + query_len {
@@ -282,8 +341,8 @@ impl StyledGraphemes {
 
     /// Replaces all occurrences of a substring `from` with another substring `to` within the `StyledGraphemes`.
     pub fn replace<S: AsRef<str>>(mut self, from: S, to: S) -> Self {
-        let from_len = from.as_ref().chars().count();
-        let to_len = to.as_ref().chars().count();
+        let from_len = from.as_ref().graphemes(true).count();
+        let to_len = to.as_ref().graphemes(true).count();
 
         let mut offset = 0;
         let diff = from_len.abs_diff(to_len);
@@ -329,7 +388,7 @@ impl StyledGraphemes {
         let mut last_was_newline = false;
 
         for styled in self.iter() {
-            if styled.ch == '\n' {
+            if matches!(styled.as_str(), "\n" | "\r\n") {
                 rows.push(row);
                 row = StyledGraphemes::default();
                 row_width = 0;
@@ -368,7 +427,11 @@ pub struct StyledGraphemesDisplay<'a> {
 impl fmt::Display for StyledGraphemesDisplay<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for styled_grapheme in self.styled_graphemes.iter() {
-            write!(f, "{}", styled_grapheme.style.apply(styled_grapheme.ch))?;
+            write!(
+                f,
+                "{}",
+                styled_grapheme.style.apply(&styled_grapheme.cluster)
+            )?;
         }
         Ok(())
     }
@@ -388,6 +451,47 @@ mod test {
             assert_eq!(3, graphemes.0.len());
             assert!(graphemes.0.iter().all(|g| g.style == style));
         }
+
+        #[test]
+        fn test_with_grapheme_clusters() {
+            // Combining mark, ZWJ sequence, flag and skin-tone modifier.
+            let graphemes = StyledGraphemes::from("e\u{301}👩\u{200d}💻🇯🇵👍🏽");
+            assert_eq!(4, graphemes.len());
+            assert_eq!("e\u{301}", graphemes.0[0].as_str());
+            assert_eq!(1, graphemes.0[0].width());
+            assert_eq!("👩\u{200d}💻", graphemes.0[1].as_str());
+            assert_eq!(2, graphemes.0[1].width());
+            assert_eq!(2, graphemes.0[2].width());
+            assert_eq!(2, graphemes.0[3].width());
+        }
+    }
+
+    mod extend {
+        use super::*;
+
+        #[test]
+        fn test_with_combining_mark() {
+            let mut grapheme = StyledGrapheme::from('e');
+            assert!(grapheme.extend('\u{301}'));
+            assert_eq!("e\u{301}", grapheme.as_str());
+            assert_eq!(1, grapheme.width());
+        }
+
+        #[test]
+        fn test_with_zwj_sequence() {
+            let mut grapheme = StyledGrapheme::from('👩');
+            assert!(grapheme.extend('\u{200d}'));
+            assert!(grapheme.extend('💻'));
+            assert_eq!("👩\u{200d}💻", grapheme.as_str());
+            assert_eq!(2, grapheme.width());
+        }
+
+        #[test]
+        fn test_with_separate_char() {
+            let mut grapheme = StyledGrapheme::from('a');
+            assert!(!grapheme.extend('b'));
+            assert_eq!("a", grapheme.as_str());
+        }
     }
 
     mod from_lines {
@@ -458,6 +562,37 @@ mod test {
         }
     }
 
+    mod apply_base_style {
+        use super::*;
+
+        use crossterm::style::{Attributes, Color};
+
+        #[test]
+        fn test() {
+            let highlight = ContentStyle {
+                foreground_color: Some(Color::Yellow),
+                ..Default::default()
+            };
+            let base = ContentStyle {
+                foreground_color: Some(Color::Grey),
+                background_color: Some(Color::Blue),
+                attributes: Attributes::from(Attribute::Bold),
+                ..Default::default()
+            };
+            let graphemes = StyledGraphemes::from("ab")
+                .apply_style_at(0, highlight)
+                .apply_base_style(base);
+            assert_eq!(
+                ContentStyle {
+                    foreground_color: Some(Color::Yellow),
+                    ..base
+                },
+                graphemes.0[0].style
+            );
+            assert_eq!(base, graphemes.0[1].style);
+        }
+    }
+
     mod apply_style_at {
         use super::*;
 
@@ -559,6 +694,13 @@ mod test {
             );
         }
 
+        #[test]
+        fn test_with_grapheme_clusters() {
+            let graphemes = StyledGraphemes::from("e\u{301}e e\u{301}");
+            assert_eq!(vec![0, 3], graphemes.find_all("e\u{301}"));
+            assert_eq!(vec![1], graphemes.find_all("e"));
+        }
+
         #[test]
         fn test_with_full_match() {
             let graphemes = StyledGraphemes::from("Hello");
@@ -657,6 +799,24 @@ mod test {
             assert_eq!("cd", rows[1].to_string());
         }
 
+        #[test]
+        fn test_wrap_wide_clusters() {
+            let input = StyledGraphemes::from("👩\u{200d}💻🇯🇵a");
+            let rows = input.wrapped_lines(3);
+            assert_eq!(rows.len(), 2);
+            assert_eq!("👩\u{200d}💻", rows[0].to_string());
+            assert_eq!("🇯🇵a", rows[1].to_string());
+        }
+
+        #[test]
+        fn test_split_by_crlf() {
+            let input = StyledGraphemes::from("ab\r\ncd");
+            let rows = input.wrapped_lines(10);
+            assert_eq!(rows.len(), 2);
+            assert_eq!("ab", rows[0].to_string());
+            assert_eq!("cd", rows[1].to_string());
+        }
+
         #[test]
         fn test_trailing_newline() {
             let input = StyledGraphemes::from("ab\n");
@@ -0,0 +1,270 @@
+//! Composes widgets into a single pane by splitting its area into rows or columns.
+//!
+//! The renderer stacks panes vertically. To place widgets side by side,
+//! e.g. a list and a preview of its selected item, compose them with [`Layout`]
+//! and render the result as one pane. Layouts are widgets themselves, so they nest.
+//!
+//! ```
+//! use promkit_core::{
+//!     grapheme::StyledGraphemes,
+//!     layout::{Layout, Size},
+//!     Widget,
+//! };
+//!
+//! let list = StyledGraphemes::from("apple\nbanana");
+//! let preview = StyledGraphemes::from("a red fruit");
+//! let pane = Layout::columns()
+//!     .push(Size::Fixed(8), &list)
+//!     .push(Size::Flex(1), &preview)
+//!     .create_graphemes(20, 5);
+//! assert_eq!("apple   a red fruit\nbanana  ", pane.to_string());
+//! ```
+
+use crate::{
+    Widget,
+    grapheme::{StyledGrapheme, StyledGraphemes},
+};
+
+/// The size of a region along the direction of its layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Size {
+    /// A fixed number of cells.
+    Fixed(u16),
+    /// A percentage of the length of the layout.
+    Percentage(u16),
+    /// A share of the length left by the fixed and percentage regions,
+    /// weighted by the value.
+    Flex(u16),
+}
+
+/// The direction the regions of a layout are placed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Regions stacked from top to bottom, sharing the height.
+    Rows,
+    /// Regions placed from left to right, sharing the width.
+    Columns,
+}
+
+/// Splits an area into regions and renders a widget in each of them,
+/// calling [`Widget::create_graphemes`] with the width and height of its region.
+///
+/// In rows, a region takes at most its height, and shorter contents leave no blank rows.
+/// In columns, the rows of each region are padded to its width to keep the next
+/// region aligned, and the layout is as tall as its tallest region.
+pub struct Layout<'a> {
+    direction: Direction,
+    regions: Vec<(Size, &'a dyn Widget)>,
+}
+
+impl<'a> Layout<'a> {
+    /// Creates a layout with no regions.
+    pub fn new(direction: Direction) -> Self {
+        Self {
+            direction,
+            regions: Vec::new(),
+        }
+    }
+
+    /// Creates a layout stacking its regions from top to bottom.
+    pub fn rows() -> Self {
+        Self::new(Direction::Rows)
+    }
+
+    /// Creates a layout placing its regions from left to right.
+    pub fn columns() -> Self {
+        Self::new(Direction::Columns)
+    }
+
+    /// Adds a region of the given size rendering the widget.
+    pub fn push(mut self, size: Size, widget: &'a dyn Widget) -> Self {
+        self.regions.push((size, widget));
+        self
+    }
+
+    /// Returns the lengths of the regions for the given length of the layout.
+    pub fn split(&self, length: u16) -> Vec<u16> {
+        split(
+            &self
+                .regions
+                .iter()
+                .map(|(size, _)| *size)
+                .collect::<Vec<_>>(),
+            length,
+        )
+    }
+}
+
+impl Widget for Layout<'_> {
+    fn create_graphemes(&self, width: u16, height: u16) -> StyledGraphemes {
+        match self.direction {
+            Direction::Rows => {
+                let rows = self
+                    .split(height)
+                    .into_iter()
+                    .zip(&self.regions)
+                    .filter(|(height, _)| *height > 0)
+                    .flat_map(|(height, (_, widget))| {
+                        widget
+                            .create_graphemes(width, height)
+                            .wrapped_lines(width as usize)
+                            .into_iter()
+                            .take(height as usize)
+                    });
+                StyledGraphemes::from_lines(rows)
+            }
+            Direction::Columns => {
+                let columns = self
+                    .split(width)
+                    .into_iter()
+                    .zip(&self.regions)
+                    .filter(|(width, _)| *width > 0)
+                    .map(|(width, (_, widget))| {
+                        let rows = widget
+                            .create_graphemes(width, height)
+                            .wrapped_lines(width as usize)
+                            .into_iter()
+                            .take(height as usize)
+                            .collect::<Vec<_>>();
+                        (width as usize, rows)
+                    })
+                    .collect::<Vec<_>>();
+                let height = columns
+                    .iter()
+                    .map(|(_, rows)| rows.len())
+                    .max()
+                    .unwrap_or(0);
+
+                StyledGraphemes::from_lines((0..height).map(|row_index| {
+                    let mut row = StyledGraphemes::default();
+                    for (column_index, (width, rows)) in columns.iter().enumerate() {
+                        let mut cells = rows.get(row_index).cloned().unwrap_or_default();
+                        let padding = width.saturating_sub(cells.widths());
+                        row.append(&mut cells);
+                        // The last column needs no padding to keep anything aligned.
+                        if column_index + 1 < columns.len() {
+                            (0..padding).for_each(|_| row.push_back(StyledGrapheme::from(' ')));
+                        }
+                    }
+                    row
+                }))
+            }
+        }
+    }
+}
+
+/// Splits the length into the lengths of regions of the given sizes.
+///
+/// Fixed and percentage sizes are given their length in order, as far as the length allows.
+/// Flexible sizes share the rest by their weights,
+/// the last of them taking the remainder of the division.
+pub fn split(sizes: &[Size], length: u16) -> Vec<u16> {
+    let mut lengths = vec![0; sizes.len()];
+    let mut rest = length;
+    for (region, size) in lengths.iter_mut().zip(sizes) {
+        let wanted = match *size {
+            Size::Fixed(cells) => cells,
+            Size::Percentage(percent) => (length as u32 * percent.min(100) as u32 / 100) as u16,
+            Size::Flex(_) => continue,
+        };
+        *region = wanted.min(rest);
+        rest -= *region;
+    }
+
+    let weights = sizes
+        .iter()
+        .map(|size| match size {
+            Size::Flex(weight) => *weight as u32,
+            _ => 0,
+        })
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<u32>();
+    if let Some(last) = weights.iter().rposition(|weight| *weight > 0) {
+        let shared = rest as u32;
+        for (region, weight) in lengths.iter_mut().zip(&weights) {
+            if *weight > 0 {
+                *region = (shared * weight / total) as u16;
+                rest -= *region;
+            }
+        }
+        lengths[last] += rest;
+    }
+    lengths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod split {
+        use super::*;
+
+        #[test]
+        fn test() {
+            assert_eq!(
+                vec![3, 5, 2],
+                split(&[Size::Fixed(3), Size::Percentage(50), Size::Flex(1)], 10)
+            );
+        }
+
+        #[test]
+        fn test_with_weights() {
+            assert_eq!(
+                vec![3, 7],
+                split(&[Size::Flex(1), Size::Flex(2)], 10),
+                "the last flexible region takes the remainder"
+            );
+        }
+
+        #[test]
+        fn test_with_insufficient_length() {
+            assert_eq!(
+                vec![4, 1, 0],
+                split(&[Size::Fixed(4), Size::Fixed(4), Size::Flex(1)], 5)
+            );
+        }
+    }
+
+    mod create_graphemes {
+        use super::*;
+
+        #[test]
+        fn test_with_columns() {
+            let left = StyledGraphemes::from("abcdef");
+            let right = StyledGraphemes::from("x\ny\nz");
+            let pane = Layout::columns()
+                .push(Size::Fixed(4), &left)
+                .push(Size::Flex(1), &right)
+                .create_graphemes(10, 2);
+            assert_eq!("abcdx\nef  y", pane.to_string());
+        }
+
+        #[test]
+        fn test_with_rows() {
+            let top = StyledGraphemes::from("a\nb\nc");
+            let bottom = StyledGraphemes::from("d");
+            let pane = Layout::rows()
+                .push(Size::Fixed(2), &top)
+                .push(Size::Flex(1), &bottom)
+                .create_graphemes(10, 5);
+            assert_eq!("a\nb\nd", pane.to_string());
+        }
+
+        #[test]
+        fn test_with_nested_layout() {
+            let (a, b, c) = (
+                StyledGraphemes::from("a"),
+                StyledGraphemes::from("b"),
+                StyledGraphemes::from("c"),
+            );
+            let right = Layout::rows()
+                .push(Size::Fixed(1), &b)
+                .push(Size::Fixed(1), &c);
+            let pane = Layout::columns()
+                .push(Size::Percentage(50), &a)
+                .push(Size::Percentage(50), &right)
+                .create_graphemes(4, 2);
+            assert_eq!("a b\n  c", pane.to_string());
+        }
+    }
+}
@@ -1,7 +1,8 @@
 pub use crossterm;
 
+pub mod backend;
 pub mod grapheme;
-// TODO: reconciliation (detecting differences between old and new grapheme trees)
+pub mod layout;
 pub mod render;
 pub mod terminal;
 
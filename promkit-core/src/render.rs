@@ -3,7 +3,11 @@ use std::sync::Arc;
 use crossbeam_skiplist::SkipMap;
 use tokio::sync::Mutex;
 
-use crate::{grapheme::StyledGraphemes, terminal::Terminal};
+use crate::{
+    backend::{Backend, Stdout},
+    grapheme::StyledGraphemes,
+    terminal::{PaneConstraint, Terminal},
+};
 
 /// SharedRenderer is a type alias for an Arc-wrapped Renderer, allowing for shared ownership and concurrency.
 pub type SharedRenderer<K> = Arc<Renderer<K>>;
@@ -12,15 +16,20 @@ pub type SharedRenderer<K> = Arc<Renderer<K>>;
 pub struct Renderer<K: Ord + Send + 'static> {
     terminal: Mutex<Terminal>,
     graphemes: SkipMap<K, StyledGraphemes>,
+    constraints: SkipMap<K, PaneConstraint>,
 }
 
 impl<K: Ord + Send + 'static> Renderer<K> {
     pub fn try_new() -> anyhow::Result<Self> {
+        Self::try_new_with_backend(Box::new(Stdout))
+    }
+
+    /// Creates a renderer drawing on the given backend instead of stdout.
+    pub fn try_new_with_backend(backend: Box<dyn Backend>) -> anyhow::Result<Self> {
         Ok(Self {
-            terminal: Mutex::new(Terminal {
-                position: crossterm::cursor::position()?,
-            }),
+            terminal: Mutex::new(Terminal::try_new(backend)?),
             graphemes: SkipMap::new(),
+            constraints: SkipMap::new(),
         })
     }
 
@@ -28,8 +37,35 @@ impl<K: Ord + Send + 'static> Renderer<K> {
     where
         I: IntoIterator<Item = (K, StyledGraphemes)>,
     {
-        let renderer = Self::try_new()?;
-        renderer.update(init);
+        Self::try_new_with_backend_and_graphemes(Box::new(Stdout), init, draw).await
+    }
+
+    pub async fn try_new_with_backend_and_graphemes<I>(
+        backend: Box<dyn Backend>,
+        init: I,
+        draw: bool,
+    ) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = (K, StyledGraphemes)>,
+    {
+        Self::try_new_with_constraints(backend, init, [], draw).await
+    }
+
+    /// Creates a renderer with the given panes and their constraints,
+    /// which decide how the panes shrink when the terminal is too short.
+    /// The panes without a constraint default to [`PaneConstraint::default`].
+    pub async fn try_new_with_constraints<I, C>(
+        backend: Box<dyn Backend>,
+        init: I,
+        constraints: C,
+        draw: bool,
+    ) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = (K, StyledGraphemes)>,
+        C: IntoIterator<Item = (K, PaneConstraint)>,
+    {
+        let renderer = Self::try_new_with_backend(backend)?;
+        renderer.update(init).constrain(constraints);
         if draw {
             renderer.render().await?;
         }
@@ -56,14 +92,33 @@ impl<K: Ord + Send + 'static> Renderer<K> {
         self
     }
 
-    // TODO: Implement diff rendering
+    /// Sets the constraints of the panes deciding how they shrink
+    /// when the terminal is too short. The panes default to [`PaneConstraint::default`].
+    pub fn constrain<I>(&self, items: I) -> &Self
+    where
+        I: IntoIterator<Item = (K, PaneConstraint)>,
+    {
+        items.into_iter().for_each(|(index, constraint)| {
+            self.constraints.insert(index, constraint);
+        });
+        self
+    }
+
+    /// Draws the current panes, emitting only the rows changed since the last render.
     pub async fn render(&self) -> anyhow::Result<()> {
-        let graphemes: Vec<StyledGraphemes> = self
+        let panes: Vec<(StyledGraphemes, PaneConstraint)> = self
             .graphemes
             .iter()
-            .map(|entry| entry.value().clone())
+            .map(|entry| {
+                let constraint = self
+                    .constraints
+                    .get(entry.key())
+                    .map(|constraint| *constraint.value())
+                    .unwrap_or_default();
+                (entry.value().clone(), constraint)
+            })
             .collect();
         let mut terminal = self.terminal.lock().await;
-        terminal.draw(&graphemes)
+        terminal.draw(&panes)
     }
 }
@@ -1,68 +1,353 @@
-use std::io::{self, Write};
+use std::io::Write;
 
 use crate::{
+    backend::Backend,
     crossterm::{cursor, style, terminal},
     grapheme::StyledGraphemes,
 };
 
+/// Bounds the rows of a pane, and decides which panes give way first
+/// when the terminal is too short to show all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaneConstraint {
+    /// Rows the pane keeps while the panes of lower priority shrink, 1 by default.
+    /// The pane is hidden only after they are.
+    pub min_height: u16,
+    /// Rows the pane takes at most. Unbounded by default.
+    pub max_height: Option<u16>,
+    /// Panes of lower priority shrink and hide first, 0 by default.
+    /// Among panes of the same priority, the lower ones give way first.
+    pub priority: i32,
+}
+
+impl Default for PaneConstraint {
+    fn default() -> Self {
+        Self {
+            min_height: 1,
+            max_height: None,
+            priority: 0,
+        }
+    }
+}
+
+impl PaneConstraint {
+    /// Sets the rows the pane keeps while the panes of lower priority shrink.
+    pub fn min_height(mut self, height: u16) -> Self {
+        self.min_height = height;
+        self
+    }
+
+    /// Sets the rows the pane takes at most.
+    pub fn max_height(mut self, height: u16) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Sets the priority of the pane.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// The rows drawn by the last call to [`Terminal::draw`],
+/// together with the terminal size at that time.
+struct Frame {
+    size: (u16, u16),
+    rows: Vec<StyledGraphemes>,
+}
+
 pub struct Terminal {
+    /// The backend the panes are drawn on.
+    backend: Box<dyn Backend>,
     /// The current cursor position within the terminal.
     pub position: (u16, u16),
+    /// The last drawn frame, used to emit only the changed rows on the next draw.
+    last_frame: Option<Frame>,
 }
 
 impl Terminal {
-    pub fn draw(&mut self, graphemes: &[StyledGraphemes]) -> anyhow::Result<()> {
-        let (width, height) = terminal::size()?;
-        let visible_height = height.saturating_sub(self.position.1);
+    /// Creates a terminal drawing on the given backend,
+    /// starting at the backend's current cursor position.
+    pub fn try_new(mut backend: Box<dyn Backend>) -> anyhow::Result<Self> {
+        let position = backend.position()?;
+        Ok(Self {
+            backend,
+            position,
+            last_frame: None,
+        })
+    }
+
+    /// Draws the panes from top to bottom.
+    /// When the terminal is too short, the panes shrink or hide by their constraints.
+    pub fn draw(&mut self, panes: &[(StyledGraphemes, PaneConstraint)]) -> anyhow::Result<()> {
+        let (width, height) = self.backend.size()?;
 
-        let viewable_rows = graphemes
+        let (viewable_rows, constraints): (Vec<_>, Vec<_>) = panes
             .iter()
-            .map(|graphemes| graphemes.wrapped_lines(width as usize))
-            .filter(|rows| !rows.is_empty())
-            .collect::<Vec<Vec<StyledGraphemes>>>();
+            .map(|(graphemes, constraint)| (graphemes.wrapped_lines(width as usize), *constraint))
+            .filter(|(rows, _)| !rows.is_empty())
+            .unzip();
 
-        if height < viewable_rows.len() as u16 {
-            return Err(anyhow::anyhow!("Insufficient space to display all panes"));
+        // Flatten the panes into the rows to draw.
+        let heights = allot(
+            &viewable_rows.iter().map(Vec::len).collect::<Vec<_>>(),
+            &constraints,
+            height as usize,
+        );
+        let rows = viewable_rows
+            .into_iter()
+            .zip(heights)
+            .flat_map(|(pane_rows, height)| pane_rows.into_iter().take(height))
+            .collect::<Vec<_>>();
+
+        // Fall back to a full redraw on the first draw and whenever the terminal is resized.
+        let previous = match self.last_frame.take() {
+            Some(frame) if frame.size == (width, height) => frame.rows,
+            _ => {
+                crossterm::queue!(
+                    self.backend,
+                    cursor::MoveTo(self.position.0, self.position.1),
+                    terminal::Clear(terminal::ClearType::FromCursorDown),
+                )?;
+                Vec::new()
+            }
+        };
+
+        for (row_index, row) in rows.iter().enumerate() {
+            // Scroll when the row would be placed below the bottom of the terminal.
+            if self.position.1 as usize + row_index >= height as usize {
+                crossterm::queue!(self.backend, terminal::ScrollUp(1))?;
+                self.position.1 = self.position.1.saturating_sub(1);
+            }
+            let y = self.position.1 + row_index as u16;
+
+            let prev = previous.get(row_index);
+            if prev == Some(row) {
+                continue;
+            }
+
+            // Skip the leading cells that are identical to the previous frame.
+            // The first row starts at the column where the prompt was placed.
+            let unchanged = prev.map(|prev| common_prefix_len(prev, row)).unwrap_or(0);
+            let offset = if row_index == 0 { self.position.0 } else { 0 };
+            let x = offset + row.iter().take(unchanged).map(|g| g.width()).sum::<usize>() as u16;
+            let changed = row
+                .iter()
+                .skip(unchanged)
+                .cloned()
+                .collect::<StyledGraphemes>();
+
+            crossterm::queue!(
+                self.backend,
+                cursor::MoveTo(x, y),
+                style::Print(changed.styled_display())
+            )?;
+
+            // Erase the leftover of the previous row when the new one is narrower.
+            // This is never needed for a full-width row, where clearing at the
+            // pending-wrap position would erase the last printed cell.
+            if prev.is_some_and(|prev| prev.widths() > row.widths()) {
+                crossterm::queue!(
+                    self.backend,
+                    terminal::Clear(terminal::ClearType::UntilNewLine)
+                )?;
+            }
+        }
+
+        let bottom = self.position.1 as usize + rows.len();
+        if rows.len() < previous.len() {
+            crossterm::queue!(
+                self.backend,
+                cursor::MoveTo(0, bottom as u16),
+                terminal::Clear(terminal::ClearType::FromCursorDown),
+            )?;
         }
 
+        // Leave the cursor on the line just below the drawn rows.
         crossterm::queue!(
-            io::stdout(),
-            cursor::MoveTo(self.position.0, self.position.1),
-            terminal::Clear(terminal::ClearType::FromCursorDown),
+            self.backend,
+            cursor::MoveTo(0, bottom.min(height.saturating_sub(1) as usize) as u16)
         )?;
+        self.backend.flush()?;
+
+        self.last_frame = Some(Frame {
+            size: (width, height),
+            rows,
+        });
+        Ok(())
+    }
+}
 
-        let mut used = 0;
+/// Returns the rows given to each pane of the given rows within the height.
+///
+/// The panes give way in ascending priority: first they shrink to their minimum heights,
+/// then they hide, except for the pane of the highest priority, which is cut to the height.
+/// The rows left after hiding panes are given back to the shrunk ones.
+fn allot(rows: &[usize], constraints: &[PaneConstraint], height: usize) -> Vec<usize> {
+    let wanted = rows
+        .iter()
+        .zip(constraints)
+        .map(|(rows, constraint)| match constraint.max_height {
+            Some(max_height) => (*rows).min(max_height as usize),
+            None => *rows,
+        })
+        .collect::<Vec<_>>();
+    let mut heights = wanted.clone();
+    let mut total = heights.iter().sum::<usize>();
 
-        let mut remaining_lines = visible_height;
+    let mut order = (0..rows.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| (constraints[index].priority, std::cmp::Reverse(index)));
+    let Some((&kept, giving_way)) = order.split_last() else {
+        return heights;
+    };
 
-        for (pane_index, rows) in viewable_rows.iter().enumerate() {
-            let max_rows = 1
-                .max((height as usize).saturating_sub(used + viewable_rows.len() - 1 - pane_index));
-            let rows = rows.iter().take(max_rows).collect::<Vec<_>>();
-            let row_count = rows.len();
-            used += row_count;
+    for &index in &order {
+        if total <= height {
+            break;
+        }
+        let min_height = (constraints[index].min_height as usize).min(heights[index]);
+        let cut = (heights[index] - min_height).min(total - height);
+        heights[index] -= cut;
+        total -= cut;
+    }
+    for &index in giving_way {
+        if total <= height {
+            break;
+        }
+        total -= heights[index];
+        heights[index] = 0;
+    }
+    if total > height {
+        heights[kept] -= total - height;
+        total = height;
+    }
 
-            for (row_index, row) in rows.iter().enumerate() {
-                crossterm::queue!(io::stdout(), style::Print(row.styled_display()))?;
+    for &index in order.iter().rev() {
+        if heights[index] > 0 {
+            let grown = (wanted[index] - heights[index]).min(height - total);
+            heights[index] += grown;
+            total += grown;
+        }
+    }
+    heights
+}
 
-                remaining_lines = remaining_lines.saturating_sub(1);
+/// Returns the number of leading graphemes that are identical (including style) in both rows.
+fn common_prefix_len(a: &StyledGraphemes, b: &StyledGraphemes) -> usize {
+    a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
+}
 
-                // Determine if scrolling is needed:
-                // - We need to scroll if we've reached the bottom of the terminal (remaining_lines == 0)
-                // - AND we have more content to display (either more rows in current pane or more panes)
-                let is_last_pane = pane_index == viewable_rows.len() - 1;
-                let is_last_row_in_pane = row_index == row_count - 1;
-                let has_more_content = !(is_last_pane && is_last_row_in_pane);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                if has_more_content && remaining_lines == 0 {
-                    crossterm::queue!(io::stdout(), terminal::ScrollUp(1))?;
-                    self.position.1 = self.position.1.saturating_sub(1);
-                }
+    mod draw {
+        use super::*;
 
-                crossterm::queue!(io::stdout(), cursor::MoveToNextLine(1))?;
-            }
+        use crate::backend::Memory;
+
+        #[test]
+        fn test_emits_only_changed_cells() {
+            let memory = Memory::new(10, 5);
+            let mut terminal = Terminal::try_new(Box::new(memory.clone())).unwrap();
+
+            terminal
+                .draw(&[(StyledGraphemes::from("abc"), PaneConstraint::default())])
+                .unwrap();
+            assert!(memory.output().contains("abc"));
+
+            let written = memory.bytes().len();
+            terminal
+                .draw(&[(StyledGraphemes::from("abd"), PaneConstraint::default())])
+                .unwrap();
+            let diff = String::from_utf8_lossy(&memory.bytes()[written..]).into_owned();
+            assert!(diff.contains('d'));
+            assert!(!diff.contains("ab"));
+        }
+    }
+
+    mod allot {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let constraints = [PaneConstraint::default(); 3];
+            assert_eq!(vec![1, 2, 3], allot(&[1, 2, 3], &constraints, 10));
+            assert_eq!(
+                vec![5, 2, 1],
+                allot(&[5, 5, 5], &constraints, 8),
+                "the lower panes shrink first"
+            );
+        }
+
+        #[test]
+        fn test_with_priority() {
+            let constraints = [
+                PaneConstraint::default(),
+                PaneConstraint::default().priority(1),
+                PaneConstraint::default(),
+            ];
+            assert_eq!(vec![1, 1, 0], allot(&[1, 3, 2], &constraints, 2));
+            assert_eq!(vec![1, 3, 1], allot(&[1, 3, 2], &constraints, 5));
+        }
+
+        #[test]
+        fn test_with_min_and_max_height() {
+            let constraints = [
+                PaneConstraint::default().max_height(2),
+                PaneConstraint::default().min_height(3).priority(1),
+                PaneConstraint::default().min_height(2),
+            ];
+            assert_eq!(vec![2, 5, 4], allot(&[4, 5, 4], &constraints, 20));
+            assert_eq!(vec![1, 3, 2], allot(&[4, 5, 4], &constraints, 6));
+            assert_eq!(
+                vec![1, 4, 0],
+                allot(&[4, 5, 4], &constraints, 5),
+                "the rows of the hidden panes are given back"
+            );
+        }
+
+        #[test]
+        fn test_with_insufficient_height() {
+            let constraints = [
+                PaneConstraint::default(),
+                PaneConstraint::default().min_height(3).priority(1),
+            ];
+            assert_eq!(vec![0, 2], allot(&[1, 3], &constraints, 2));
+        }
+    }
+
+    mod common_prefix_len {
+        use super::*;
+
+        use crossterm::style::{Color, ContentStyle};
+
+        #[test]
+        fn test() {
+            let a = StyledGraphemes::from("abcdef");
+            let b = StyledGraphemes::from("abcxef");
+            assert_eq!(3, common_prefix_len(&a, &b));
+        }
+
+        #[test]
+        fn test_with_different_style() {
+            let a = StyledGraphemes::from("abc");
+            let b = StyledGraphemes::from("abc").apply_style_at(
+                1,
+                ContentStyle {
+                    foreground_color: Some(Color::Red),
+                    ..Default::default()
+                },
+            );
+            assert_eq!(1, common_prefix_len(&a, &b));
+        }
+
+        #[test]
+        fn test_with_prefix() {
+            let a = StyledGraphemes::from("ab");
+            let b = StyledGraphemes::from("abcd");
+            assert_eq!(2, common_prefix_len(&a, &b));
         }
-        io::stdout().flush()?;
-        Ok(())
     }
 }
@@ -0,0 +1,156 @@
+use promkit_core::{
+    Widget,
+    grapheme::{StyledGrapheme, StyledGraphemes},
+};
+
+#[path = "editor/editor.rs"]
+mod inner;
+pub use inner::Editor;
+pub mod config;
+pub use config::Config;
+
+/// A multi-line text editor widget.
+///
+/// Each logical line is soft-wrapped to the width,
+/// optionally with the line number in a gutter,
+/// and the view scrolls to keep the cursor within `lines`.
+#[derive(Clone, Default)]
+pub struct State {
+    /// The `Editor` component to be rendered.
+    pub editor: Editor,
+    /// Configuration for rendering and behavior.
+    pub config: Config,
+}
+
+impl State {
+    /// Splits the text into logical lines with the styles applied,
+    /// returning them with the line and column of the cursor.
+    fn styled_lines(&self) -> (Vec<StyledGraphemes>, (usize, usize)) {
+        let position = self.editor.position();
+        let mut lines = vec![StyledGraphemes::default()];
+        let mut cursor = (0, 0);
+
+        for (i, grapheme) in self.editor.text().iter().enumerate() {
+            let line = lines.len() - 1;
+            let is_newline = matches!(grapheme.as_str(), "\n" | "\r\n");
+            if i == position {
+                cursor = (line, lines[line].len());
+            }
+            if is_newline {
+                // Show the cursor on a line break as a blank cell at the end of the line.
+                if i == position {
+                    lines[line].push_back(StyledGrapheme::new(" ", self.config.active_char_style));
+                }
+                lines.push(StyledGraphemes::default());
+                continue;
+            }
+
+            let mut grapheme = grapheme.clone();
+            grapheme.apply_style(if i == position {
+                self.config.active_char_style
+            } else {
+                self.config.inactive_char_style
+            });
+            lines[line].push_back(grapheme);
+        }
+
+        (lines, cursor)
+    }
+}
+
+impl Widget for State {
+    fn create_graphemes(&self, width: u16, height: u16) -> StyledGraphemes {
+        let height = match self.config.lines {
+            Some(lines) => lines.min(height as usize),
+            None => height as usize,
+        };
+        if width == 0 || height == 0 {
+            return StyledGraphemes::default();
+        }
+
+        let (lines, cursor) = self.styled_lines();
+
+        let number_width = if self.config.line_numbers {
+            lines.len().to_string().len()
+        } else {
+            0
+        };
+        let gutter = |number: Option<usize>| match number {
+            Some(number) if self.config.line_numbers => StyledGraphemes::from_str(
+                format!("{number:>number_width$} "),
+                self.config.line_number_style,
+            ),
+            _ if self.config.line_numbers => StyledGraphemes::from(" ".repeat(number_width + 1)),
+            _ => StyledGraphemes::default(),
+        };
+        let gutter_width = if self.config.line_numbers {
+            number_width + 1
+        } else {
+            0
+        };
+        let text_width = (width as usize).saturating_sub(gutter_width).max(1);
+
+        let mut rows = Vec::new();
+        let mut cursor_row = 0;
+        for (i, line) in lines.iter().enumerate() {
+            let mut row = StyledGraphemes::default();
+            let mut row_width = 0;
+            let mut number = Some(i + 1);
+            for (j, grapheme) in line.iter().enumerate() {
+                if !row.is_empty() && row_width + grapheme.width() > text_width {
+                    rows.push(StyledGraphemes::from_iter([&gutter(number.take()), &row]));
+                    row = StyledGraphemes::default();
+                    row_width = 0;
+                }
+                if (i, j) == cursor {
+                    cursor_row = rows.len();
+                }
+                row.push_back(grapheme.clone());
+                row_width += grapheme.width();
+            }
+            rows.push(StyledGraphemes::from_iter([&gutter(number.take()), &row]));
+        }
+
+        let start = (cursor_row + 1).saturating_sub(height);
+        StyledGraphemes::from_lines(rows.into_iter().skip(start).take(height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod create_graphemes {
+        use super::*;
+
+        #[test]
+        fn test_with_line_numbers_and_wrap() {
+            let state = State {
+                editor: Editor::new("abcdef\n\nx"),
+                config: Config {
+                    line_numbers: true,
+                    ..Default::default()
+                },
+            };
+            assert_eq!(
+                "1 abcd\n  ef\n2 \n3 x ",
+                state.create_graphemes(6, 10).to_string()
+            );
+        }
+
+        #[test]
+        fn test_scrolls_to_cursor() {
+            let mut state = State {
+                editor: Editor::new("1\n2\n3\n4"),
+                config: Config {
+                    lines: Some(2),
+                    ..Default::default()
+                },
+            };
+            assert_eq!("3\n4 ", state.create_graphemes(10, 10).to_string());
+
+            state.editor.move_to_head();
+            assert_eq!("1\n2", state.create_graphemes(10, 10).to_string());
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::HashSet;
+
+use promkit_core::crossterm::style::ContentStyle;
+
+use crate::text_editor::Mode;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Default)]
+pub struct Config {
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "termcfg::crossterm_config::content_style_serde")
+    )]
+    pub active_char_style: ContentStyle,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "termcfg::crossterm_config::content_style_serde")
+    )]
+    pub inactive_char_style: ContentStyle,
+    /// Whether to show the line numbers in a gutter on the left.
+    pub line_numbers: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "termcfg::crossterm_config::content_style_serde")
+    )]
+    pub line_number_style: ContentStyle,
+    pub edit_mode: Mode,
+    pub word_break_chars: HashSet<char>,
+    pub lines: Option<usize>,
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use promkit_core::grapheme::StyledGraphemes;
+
+use crate::text_editor::TextEditor;
+
+/// A multi-line text editor.
+///
+/// The text is kept in a [`TextEditor`] with newlines as graphemes,
+/// so editing, word motions and undo/redo work the same as in a single line.
+/// On top of that, it moves the cursor between logical lines,
+/// keeping the column when passing through shorter lines.
+#[derive(Clone, Default)]
+pub struct Editor {
+    texteditor: TextEditor,
+    /// The column to return to while moving up and down.
+    goal_column: Option<usize>,
+}
+
+fn is_newline(grapheme: &str) -> bool {
+    matches!(grapheme, "\n" | "\r\n")
+}
+
+impl Editor {
+    /// Creates an editor with the given text and the cursor at the end.
+    pub fn new<S: AsRef<str>>(s: S) -> Self {
+        Self {
+            texteditor: TextEditor::new(s),
+            goal_column: None,
+        }
+    }
+
+    /// Returns the underlying text editor.
+    pub fn texteditor(&self) -> &TextEditor {
+        &self.texteditor
+    }
+
+    /// Returns the underlying text editor for editing it directly.
+    pub fn texteditor_mut(&mut self) -> &mut TextEditor {
+        self.goal_column = None;
+        &mut self.texteditor
+    }
+
+    /// Returns the current text including the cursor.
+    pub fn text(&self) -> StyledGraphemes {
+        self.texteditor.text()
+    }
+
+    /// Returns the text without the cursor.
+    pub fn text_without_cursor(&self) -> StyledGraphemes {
+        self.texteditor.text_without_cursor()
+    }
+
+    /// Returns the current position of the cursor within the text.
+    pub fn position(&self) -> usize {
+        self.texteditor.position()
+    }
+
+    /// Returns the grapheme ranges of the logical lines, excluding the newlines.
+    fn line_ranges(&self) -> Vec<std::ops::Range<usize>> {
+        let text = self.text_without_cursor();
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for (i, g) in text.iter().enumerate() {
+            if is_newline(g.as_str()) {
+                ranges.push(start..i);
+                start = i + 1;
+            }
+        }
+        ranges.push(start..text.len());
+        ranges
+    }
+
+    /// Returns the logical lines of the text.
+    pub fn lines(&self) -> Vec<String> {
+        let text = self.text_without_cursor();
+        self.line_ranges()
+            .into_iter()
+            .map(|range| {
+                text.iter()
+                    .skip(range.start)
+                    .take(range.len())
+                    .map(|g| g.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the logical line and the column (in graphemes) of the cursor.
+    pub fn cursor_line_column(&self) -> (usize, usize) {
+        let pos = self.position();
+        let ranges = self.line_ranges();
+        let line = ranges
+            .iter()
+            .position(|range| pos <= range.end)
+            .unwrap_or(ranges.len() - 1);
+        (line, pos - ranges[line].start)
+    }
+
+    /// Inserts a character at the current cursor position.
+    pub fn insert(&mut self, ch: char) {
+        self.texteditor_mut().insert(ch);
+    }
+
+    /// Inserts a line break at the current cursor position.
+    pub fn insert_newline(&mut self) {
+        self.texteditor_mut().insert('\n');
+    }
+
+    /// Overwrites the character at the current cursor position,
+    /// inserting instead at the end of a line.
+    pub fn overwrite(&mut self, ch: char) {
+        let at_line_end = self
+            .text()
+            .iter()
+            .nth(self.position())
+            .is_some_and(|g| is_newline(g.as_str()));
+        if at_line_end {
+            self.insert(ch)
+        } else {
+            self.texteditor_mut().overwrite(ch)
+        }
+    }
+
+    /// Erases the character before the cursor, joining the lines at a line head.
+    pub fn erase(&mut self) {
+        self.texteditor_mut().erase();
+    }
+
+    /// Erases the text back to the nearest previous character in `word_break_chars`.
+    pub fn erase_to_previous_nearest(&mut self, word_break_chars: &HashSet<char>) -> String {
+        self.texteditor_mut()
+            .erase_to_previous_nearest(word_break_chars)
+    }
+
+    /// Erases the text from the cursor to the end of the line.
+    /// At the end of a line, the line break is erased instead.
+    pub fn erase_to_line_tail(&mut self) -> String {
+        let (line, _) = self.cursor_line_column();
+        let end = self.line_ranges()[line].end;
+        let pos = self.position();
+        let end = if end == pos { end + 1 } else { end };
+        self.texteditor_mut().erase_range(pos..end)
+    }
+
+    /// Moves the cursor one position backward, if possible.
+    pub fn backward(&mut self) -> bool {
+        self.texteditor_mut().backward()
+    }
+
+    /// Moves the cursor one position forward, if possible.
+    pub fn forward(&mut self) -> bool {
+        self.texteditor_mut().forward()
+    }
+
+    /// Moves the cursor to the previous line, keeping the column if possible.
+    /// Returns `false` on the first line.
+    pub fn up(&mut self) -> bool {
+        let (line, column) = self.cursor_line_column();
+        match line.checked_sub(1) {
+            Some(target) => {
+                self.move_vertically(target, column);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the cursor to the next line, keeping the column if possible.
+    /// Returns `false` on the last line.
+    pub fn down(&mut self) -> bool {
+        let (line, column) = self.cursor_line_column();
+        if line + 1 < self.line_ranges().len() {
+            self.move_vertically(line + 1, column);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn move_vertically(&mut self, target: usize, column: usize) {
+        let goal = *self.goal_column.get_or_insert(column);
+        let range = &self.line_ranges()[target];
+        self.texteditor.move_to(range.start + goal.min(range.len()));
+    }
+
+    /// Moves the cursor to the beginning of the current line.
+    pub fn move_to_line_head(&mut self) {
+        let (line, _) = self.cursor_line_column();
+        let start = self.line_ranges()[line].start;
+        self.texteditor_mut().move_to(start);
+    }
+
+    /// Moves the cursor to the end of the current line.
+    pub fn move_to_line_tail(&mut self) {
+        let (line, _) = self.cursor_line_column();
+        let end = self.line_ranges()[line].end;
+        self.texteditor_mut().move_to(end);
+    }
+
+    /// Moves the cursor to the beginning of the text.
+    pub fn move_to_head(&mut self) {
+        self.texteditor_mut().move_to_head()
+    }
+
+    /// Moves the cursor to the end of the text.
+    pub fn move_to_tail(&mut self) {
+        self.texteditor_mut().move_to_tail()
+    }
+
+    /// Reverts the last edit. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.texteditor_mut().undo()
+    }
+
+    /// Re-applies the last undone edit. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.texteditor_mut().redo()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod cursor_line_column {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut editor = Editor::new("ab\ncde\n");
+            assert_eq!((2, 0), editor.cursor_line_column());
+            editor.backward();
+            assert_eq!((1, 3), editor.cursor_line_column());
+            editor.move_to_head();
+            assert_eq!((0, 0), editor.cursor_line_column());
+            assert_eq!(vec!["ab", "cde", ""], editor.lines());
+        }
+    }
+
+    mod up_and_down {
+        use super::*;
+
+        #[test]
+        fn test_keeps_goal_column() {
+            let mut editor = Editor::new("abcd\nx\nefgh");
+            assert_eq!((2, 4), editor.cursor_line_column());
+            assert!(editor.up());
+            assert_eq!((1, 1), editor.cursor_line_column());
+            assert!(editor.up());
+            assert_eq!((0, 4), editor.cursor_line_column());
+            assert!(!editor.up());
+            assert!(editor.down());
+            assert!(editor.down());
+            assert_eq!((2, 4), editor.cursor_line_column());
+            assert!(!editor.down());
+        }
+
+        #[test]
+        fn test_resets_goal_column_on_edit() {
+            let mut editor = Editor::new("abcd\nx");
+            editor.up();
+            editor.down();
+            editor.insert('y');
+            editor.up();
+            assert_eq!((0, 2), editor.cursor_line_column());
+        }
+    }
+
+    mod erase_to_line_tail {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut editor = Editor::new("ab\ncd");
+            editor.move_to_head();
+            editor.forward();
+            assert_eq!("b", editor.erase_to_line_tail());
+            assert_eq!("\n", editor.erase_to_line_tail());
+            assert_eq!(vec!["acd"], editor.lines());
+        }
+    }
+
+    mod move_to_line_head_and_tail {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut editor = Editor::new("ab\ncd");
+            editor.up();
+            editor.move_to_line_head();
+            assert_eq!(0, editor.position());
+            editor.move_to_line_tail();
+            assert_eq!(2, editor.position());
+        }
+    }
+}
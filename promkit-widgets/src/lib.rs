@@ -8,6 +8,10 @@ pub mod cursor;
 #[cfg_attr(docsrs, doc(cfg(feature = "checkbox")))]
 pub mod checkbox;
 
+#[cfg(feature = "editor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "editor")))]
+pub mod editor;
+
 #[cfg(feature = "jsonstream")]
 #[cfg_attr(docsrs, doc(cfg(feature = "jsonstream")))]
 pub mod jsonstream;
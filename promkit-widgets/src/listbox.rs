@@ -29,7 +29,8 @@ impl Widget for State {
             .items()
             .iter()
             .enumerate()
-            .filter(|(i, _)| *i >= self.listbox.position() && *i < self.listbox.position() + height)
+            .skip(self.listbox.position())
+            .take(height)
             .map(|(i, item)| {
                 if i == self.listbox.position() {
                     let init = StyledGraphemes::from_iter([
@@ -37,7 +38,7 @@ impl Widget for State {
                         item,
                     ]);
                     if let Some(style) = &self.config.active_item_style {
-                        init.apply_style(*style)
+                        init.apply_base_style(*style)
                     } else {
                         init
                     }
@@ -49,7 +50,7 @@ impl Widget for State {
                         item,
                     ]);
                     if let Some(style) = &self.config.inactive_item_style {
-                        init.apply_style(*style)
+                        init.apply_base_style(*style)
                     } else {
                         init
                     }
@@ -2,6 +2,10 @@ use promkit_core::{Widget, grapheme::StyledGraphemes};
 
 mod history;
 pub use history::History;
+mod history_file;
+pub use history_file::{HistoryEntry, HistoryFile};
+mod kill_ring;
+pub use kill_ring::KillRing;
 #[path = "text_editor/text_editor.rs"]
 mod inner;
 pub use inner::{Mode, TextEditor};
@@ -14,11 +18,104 @@ pub struct State {
     pub texteditor: TextEditor,
     /// Optional history for navigating through previous inputs.
     pub history: Option<History>,
+    /// The kill ring holding the text removed by kill operations.
+    pub kill_ring: KillRing,
+    /// Text suggested to follow the input,
+    /// shown with `autosuggestion_style` while the cursor is at the end.
+    pub autosuggestion: Option<String>,
 
     /// Configuration for rendering and behavior.
     pub config: Config,
 }
 
+impl State {
+    /// Returns the autosuggestion if it is shown,
+    /// i.e. the cursor is at the end and the input is not masked.
+    fn visible_autosuggestion(&self) -> Option<&str> {
+        self.autosuggestion
+            .as_deref()
+            .filter(|suggestion| !suggestion.is_empty())
+            .filter(|_| self.config.mask.is_none())
+            .filter(|_| self.texteditor.position() + 1 == self.texteditor.text().len())
+    }
+
+    /// Appends the autosuggestion to the text.
+    /// Returns `false` if no autosuggestion is shown.
+    pub fn accept_autosuggestion(&mut self) -> bool {
+        let Some(suggestion) = self.visible_autosuggestion().map(str::to_owned) else {
+            return false;
+        };
+        self.texteditor.insert_str(&suggestion);
+        self.autosuggestion = None;
+        true
+    }
+
+    /// Kills the text from the cursor to the end of the line.
+    pub fn kill_to_tail(&mut self) {
+        let killed = self.texteditor.erase_to_tail();
+        self.kill_ring.push(killed);
+    }
+
+    /// Kills the text from the beginning of the line to the cursor.
+    pub fn kill_to_head(&mut self) {
+        let killed = self.texteditor.erase_to_head();
+        self.kill_ring.push(killed);
+    }
+
+    /// Kills the whole line.
+    pub fn kill_all(&mut self) {
+        let killed = self.texteditor.erase_all();
+        self.kill_ring.push(killed);
+    }
+
+    /// Kills the text back to the nearest previous word break character.
+    pub fn kill_to_previous_nearest(&mut self) {
+        let killed = self
+            .texteditor
+            .erase_to_previous_nearest(&self.config.word_break_chars);
+        self.kill_ring.push(killed);
+    }
+
+    /// Kills the text forward to the nearest next word break character.
+    pub fn kill_to_next_nearest(&mut self) {
+        let killed = self
+            .texteditor
+            .erase_to_next_nearest(&self.config.word_break_chars);
+        self.kill_ring.push(killed);
+    }
+
+    /// Inserts the most recently killed text at the cursor.
+    /// Returns `false` if the kill ring is empty.
+    pub fn yank(&mut self) -> bool {
+        let Some(text) = self.kill_ring.reset().map(str::to_owned) else {
+            return false;
+        };
+        let start = self.texteditor.position();
+        self.texteditor.insert_str(&text);
+        self.kill_ring.last_yank =
+            Some((start..self.texteditor.position(), self.texteditor.text()));
+        true
+    }
+
+    /// Replaces the text just yanked with the next older kill.
+    /// Returns `false` unless the previous edit was a yank.
+    pub fn yank_pop(&mut self) -> bool {
+        let Some((range, _)) = self.kill_ring.last_yank.take().filter(|(range, text)| {
+            range.end == self.texteditor.position() && *text == self.texteditor.text()
+        }) else {
+            return false;
+        };
+        let Some(text) = self.kill_ring.rotate().map(str::to_owned) else {
+            return false;
+        };
+        let start = range.start;
+        self.texteditor.replace_range(range, &text);
+        self.kill_ring.last_yank =
+            Some((start..self.texteditor.position(), self.texteditor.text()));
+        true
+    }
+}
+
 impl Widget for State {
     fn create_graphemes(&self, width: u16, height: u16) -> StyledGraphemes {
         if width == 0 {
@@ -38,9 +135,17 @@ impl Widget for State {
             None => self.texteditor.text(),
         };
 
-        let mut styled = text
-            .apply_style(self.config.inactive_char_style)
-            .apply_style_at(self.texteditor.position(), self.config.active_char_style);
+        let mut styled = text.clone().apply_style(self.config.inactive_char_style);
+        // Show the autosuggestion from the cursor cell onward.
+        if let Some(suggestion) = self.visible_autosuggestion() {
+            styled.pop_back();
+            styled.append(&mut StyledGraphemes::from_str(
+                suggestion,
+                self.config.autosuggestion_style,
+            ));
+        }
+        let mut styled =
+            styled.apply_style_at(self.texteditor.position(), self.config.active_char_style);
 
         buf.append(&mut styled);
 
@@ -55,7 +160,12 @@ impl Widget for State {
         }
 
         let lines = rows.len().min(height);
-        let mut start = (prefix_width + self.texteditor.position()) / width as usize;
+        let cursor_offset = text
+            .iter()
+            .take(self.texteditor.position())
+            .map(|g| g.width())
+            .sum::<usize>();
+        let mut start = (prefix_width + cursor_offset) / width as usize;
         let end = start + lines;
         if end > rows.len() {
             start = rows.len().saturating_sub(lines);
@@ -64,3 +174,105 @@ impl Widget for State {
         StyledGraphemes::from_lines(rows.into_iter().skip(start).take(lines))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    mod autosuggestion {
+        use super::*;
+
+        #[test]
+        fn test_shown_at_tail() {
+            let mut state = State {
+                texteditor: TextEditor::new("car"),
+                autosuggestion: Some(String::from("go build")),
+                ..Default::default()
+            };
+            assert_eq!("cargo build", state.create_graphemes(20, 1).to_string());
+
+            state.texteditor.backward();
+            assert_eq!("car ", state.create_graphemes(20, 1).to_string());
+            assert!(!state.accept_autosuggestion());
+        }
+
+        #[test]
+        fn test_accept() {
+            let mut state = State {
+                texteditor: TextEditor::new("car"),
+                autosuggestion: Some(String::from("go")),
+                ..Default::default()
+            };
+            assert!(state.accept_autosuggestion());
+            assert_eq!("cargo", state.texteditor.text_without_cursor().to_string());
+            assert!(!state.accept_autosuggestion());
+        }
+    }
+
+    mod yank {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut state = State {
+                texteditor: TextEditor::new("hello world"),
+                config: Config {
+                    word_break_chars: HashSet::from([' ']),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert!(!state.yank());
+
+            state.kill_to_previous_nearest();
+            state.texteditor.move_to_head();
+            assert!(state.yank());
+            assert_eq!(
+                "worldhello ",
+                state.texteditor.text_without_cursor().to_string()
+            );
+            assert_eq!(5, state.texteditor.position());
+        }
+    }
+
+    mod yank_pop {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut state = State {
+                texteditor: TextEditor::new("one two"),
+                config: Config {
+                    word_break_chars: HashSet::from([' ']),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            state.kill_to_previous_nearest();
+            state.kill_all();
+            assert_eq!(2, state.kill_ring.len());
+
+            assert!(state.yank());
+            assert_eq!("one ", state.texteditor.text_without_cursor().to_string());
+            assert!(state.yank_pop());
+            assert_eq!("two", state.texteditor.text_without_cursor().to_string());
+            assert!(state.yank_pop());
+            assert_eq!("one ", state.texteditor.text_without_cursor().to_string());
+        }
+
+        #[test]
+        fn test_after_other_edit() {
+            let mut state = State::default();
+            state.kill_ring.push("a");
+            state.kill_ring.push("b");
+            assert!(!state.yank_pop());
+
+            state.yank();
+            state.texteditor.insert('c');
+            assert!(!state.yank_pop());
+            assert_eq!("bc", state.texteditor.text_without_cursor().to_string());
+        }
+    }
+}
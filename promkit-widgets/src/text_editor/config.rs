@@ -25,6 +25,11 @@ pub struct Config {
         serde(with = "termcfg::crossterm_config::content_style_serde")
     )]
     pub inactive_char_style: ContentStyle,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "termcfg::crossterm_config::content_style_serde")
+    )]
+    pub autosuggestion_style: ContentStyle,
     pub edit_mode: Mode,
     pub word_break_chars: HashSet<char>,
     pub lines: Option<usize>,
@@ -48,6 +53,7 @@ mask = "*"
 prefix_style = "fg=green,attr=bold"
 active_char_style = "bg=darkcyan,attr=underlined"
 inactive_char_style = "fg=grey"
+autosuggestion_style = "fg=darkgrey"
 edit_mode = "Overwrite"
 word_break_chars = [" ", ".", "/"]
 lines = 3
@@ -72,6 +78,10 @@ lines = 3
                 formatter.inactive_char_style.foreground_color,
                 Some(Color::Grey),
             );
+            assert_eq!(
+                formatter.autosuggestion_style.foreground_color,
+                Some(Color::DarkGrey),
+            );
             assert!(matches!(formatter.edit_mode, Mode::Overwrite));
             assert_eq!(formatter.word_break_chars, HashSet::from([' ', '.', '/']));
             assert_eq!(formatter.lines, Some(3));
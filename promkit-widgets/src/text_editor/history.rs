@@ -181,6 +181,47 @@ impl History {
     pub fn move_to_tail(&mut self) {
         self.cursor.move_to_tail()
     }
+
+    /// Returns the most recent entry that starts with `prefix` and is longer than it.
+    pub fn latest_starting_with<T: AsRef<str>>(&self, prefix: T) -> Option<&str> {
+        let prefix = prefix.as_ref();
+        self.cursor
+            .contents()
+            .iter()
+            .rev()
+            .find(|item| item.len() > prefix.len() && item.starts_with(prefix))
+            .map(String::as_str)
+    }
+
+    /// Moves to the nearest older entry containing `query`, skipping the current one.
+    /// Returns `false` without moving if there is no such entry.
+    pub fn search_backward<T: AsRef<str>>(&mut self, query: T) -> bool {
+        let found = self
+            .cursor
+            .contents()
+            .iter()
+            .take(self.cursor.position())
+            .rposition(|item| item.contains(query.as_ref()));
+        found.is_some_and(|position| self.cursor.move_to(position))
+    }
+
+    /// Moves to the nearest newer entry containing `query`, skipping the current one.
+    /// The empty entry at the tail is not searched.
+    /// Returns `false` without moving if there is no such entry.
+    pub fn search_forward<T: AsRef<str>>(&mut self, query: T) -> bool {
+        let tail = self.cursor.contents().len() - 1;
+        let start = self.cursor.position() + 1;
+        let found = self
+            .cursor
+            .contents()
+            .iter()
+            .enumerate()
+            .take(tail)
+            .skip(start)
+            .find(|(_, item)| item.contains(query.as_ref()))
+            .map(|(position, _)| position);
+        found.is_some_and(|position| self.cursor.move_to(position))
+    }
 }
 
 #[cfg(test)]
@@ -225,6 +266,57 @@ mod test {
         }
     }
 
+    mod latest_starting_with {
+        use super::super::*;
+
+        #[test]
+        fn test() {
+            let mut h = History::default();
+            h.insert("cargo build");
+            h.insert("cargo test");
+            h.insert("cargo");
+            assert_eq!(Some("cargo test"), h.latest_starting_with("cargo"));
+            assert_eq!(Some("cargo build"), h.latest_starting_with("cargo b"));
+            assert_eq!(None, h.latest_starting_with("git"));
+        }
+    }
+
+    mod search_backward {
+        use super::super::*;
+
+        #[test]
+        fn test() {
+            let mut h = History::default();
+            h.insert("cargo build");
+            h.insert("git status");
+            h.insert("cargo test");
+            assert!(h.search_backward("cargo"));
+            assert_eq!("cargo test", h.get());
+            assert!(h.search_backward("cargo"));
+            assert_eq!("cargo build", h.get());
+            assert!(!h.search_backward("cargo"));
+            assert_eq!("cargo build", h.get());
+        }
+    }
+
+    mod search_forward {
+        use super::super::*;
+
+        #[test]
+        fn test() {
+            let mut h = History::default();
+            h.insert("cargo build");
+            h.insert("git status");
+            h.insert("cargo test");
+            assert!(!h.search_forward("cargo"));
+            while h.backward() {}
+            assert!(h.search_forward("cargo"));
+            assert_eq!("cargo test", h.get());
+            assert!(!h.search_forward(""));
+            assert_eq!("cargo test", h.get());
+        }
+    }
+
     mod exists {
         use super::super::*;
 
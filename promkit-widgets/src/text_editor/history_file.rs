@@ -0,0 +1,427 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::History;
+
+/// The first line of a history file, telling it from a file
+/// written by [`History::save_to_file`].
+const HEADER: &str = "#promkit-history v1";
+
+/// An entry recorded in a [`HistoryFile`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The input text, which may span multiple lines.
+    pub text: String,
+    /// Seconds since the Unix epoch when the entry was recorded.
+    pub timestamp: Option<u64>,
+    /// The working directory the entry was recorded in.
+    pub cwd: Option<PathBuf>,
+}
+
+impl HistoryEntry {
+    /// Formats the entry as a single line of
+    /// tab-separated timestamp, working directory and text.
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}",
+            self.timestamp.map(|t| t.to_string()).unwrap_or_default(),
+            escape(
+                &self
+                    .cwd
+                    .as_ref()
+                    .map(|cwd| cwd.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            ),
+            escape(&self.text),
+        )
+    }
+
+    /// Parses a line written by `to_line`.
+    /// A malformed line is read as the bare text.
+    fn from_line(line: &str) -> Self {
+        let mut fields = line.splitn(3, '\t');
+        if let (Some(timestamp), Some(cwd), Some(text)) =
+            (fields.next(), fields.next(), fields.next())
+            && (timestamp.is_empty() || timestamp.parse::<u64>().is_ok())
+        {
+            return Self {
+                text: unescape(text),
+                timestamp: timestamp.parse().ok(),
+                cwd: Some(unescape(cwd))
+                    .filter(|cwd| !cwd.is_empty())
+                    .map(PathBuf::from),
+            };
+        }
+        Self {
+            text: line.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Escapes backslashes, tabs and line breaks so that a field stays on one line.
+fn escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\\' => ret.push_str("\\\\"),
+            '\t' => ret.push_str("\\t"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            ch => ret.push(ch),
+        }
+    }
+    ret
+}
+
+/// Reverts `escape`.
+fn unescape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            ret.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => ret.push('\t'),
+            Some('n') => ret.push('\n'),
+            Some('r') => ret.push('\r'),
+            Some(ch) => ret.push(ch),
+            None => ret.push('\\'),
+        }
+    }
+    ret
+}
+
+/// A history persisted to a file, shared between sessions.
+///
+/// Each entry is appended as one line with its timestamp and,
+/// optionally, the working directory, escaping line breaks so that
+/// multi-line entries survive a round trip.
+/// A file written by [`History::save_to_file`] is read as well,
+/// and converted to this format on the first append.
+/// Appending takes an exclusive lock on the file,
+/// so concurrent sessions merge their entries instead of overwriting each other.
+#[derive(Clone, Debug)]
+pub struct HistoryFile {
+    path: PathBuf,
+    /// Whether to skip an entry equal to the most recent one in the file.
+    pub ignore_duplicates: bool,
+    /// Whether to skip entries starting with a space.
+    pub ignore_space: bool,
+    /// Whether to record the current working directory with each entry.
+    pub record_cwd: bool,
+    /// Optional limit on the number of entries loaded into a [`History`]
+    /// and kept by [`HistoryFile::compact`].
+    pub limit_size: Option<usize>,
+}
+
+impl HistoryFile {
+    /// Creates a history file at the given path, which is created on the first append.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            ignore_duplicates: true,
+            ignore_space: false,
+            record_cwd: false,
+            limit_size: None,
+        }
+    }
+
+    /// Returns the path to the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets whether to skip an entry equal to the most recent one.
+    pub fn ignore_duplicates(mut self, enabled: bool) -> Self {
+        self.ignore_duplicates = enabled;
+        self
+    }
+
+    /// Sets whether to skip entries starting with a space.
+    pub fn ignore_space(mut self, enabled: bool) -> Self {
+        self.ignore_space = enabled;
+        self
+    }
+
+    /// Sets whether to record the current working directory with each entry.
+    pub fn record_cwd(mut self, enabled: bool) -> Self {
+        self.record_cwd = enabled;
+        self
+    }
+
+    /// Sets the limit on the number of entries.
+    pub fn limit_size(mut self, limit_size: usize) -> Self {
+        self.limit_size = Some(limit_size);
+        self
+    }
+
+    /// Reads all entries, oldest first.
+    /// Returns no entries if the file does not exist yet.
+    pub fn load(&self) -> anyhow::Result<Vec<HistoryEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        file.lock_shared()?;
+        Ok(Self::read(&file)?.0)
+    }
+
+    /// Loads the entries into a new `History`,
+    /// keeping the most recent `limit_size` of them.
+    pub fn load_history(&self) -> anyhow::Result<History> {
+        let entries = self.load()?;
+        let mut ret = History::default();
+        ret.limit_size = self.limit_size;
+        for entry in entries {
+            ret.insert(entry.text);
+        }
+        Ok(ret)
+    }
+
+    /// Appends an entry unless it is skipped by the policies.
+    /// Returns whether the entry was recorded.
+    pub fn append<T: AsRef<str>>(&self, text: T) -> anyhow::Result<bool> {
+        let text = text.as_ref();
+        if text.is_empty() || (self.ignore_space && text.starts_with(' ')) {
+            return Ok(false);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        file.lock()?;
+
+        // Read under the lock, so that an entry appended by another session counts.
+        let (entries, current) = Self::read(&file)?;
+        if self.ignore_duplicates && entries.last().is_some_and(|entry| entry.text == text) {
+            return Ok(false);
+        }
+        if !current {
+            Self::write(&mut file, &entries)?;
+        }
+
+        let entry = HistoryEntry {
+            text: text.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_secs()),
+            cwd: if self.record_cwd {
+                std::env::current_dir().ok()
+            } else {
+                None
+            },
+        };
+        writeln!(file, "{}", entry.to_line())?;
+        Ok(true)
+    }
+
+    /// Rewrites the file, removing older duplicates when `ignore_duplicates` is set
+    /// and keeping the most recent `limit_size` entries.
+    pub fn compact(&self) -> anyhow::Result<()> {
+        let mut file = match OpenOptions::new().read(true).write(true).open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        file.lock()?;
+
+        let (mut entries, _) = Self::read(&file)?;
+        if self.ignore_duplicates {
+            let mut seen = std::collections::HashSet::new();
+            entries.reverse();
+            entries.retain(|entry| seen.insert(entry.text.clone()));
+            entries.reverse();
+        }
+        if let Some(limit) = self.limit_size {
+            entries.drain(..entries.len().saturating_sub(limit));
+        }
+
+        Self::write(&mut file, &entries)
+    }
+
+    /// Reads the entries, oldest first, and whether the file is in the current format.
+    /// The lines of a file written by [`History::save_to_file`],
+    /// newest first, are read as the bare text.
+    fn read(mut file: &File) -> anyhow::Result<(Vec<HistoryEntry>, bool)> {
+        file.seek(SeekFrom::Start(0))?;
+        let mut lines = BufReader::new(file).lines().peekable();
+        let current = match lines.peek() {
+            Some(Ok(line)) => line == HEADER,
+            Some(Err(_)) => false,
+            None => return Ok((Vec::new(), false)),
+        };
+        if current {
+            lines.next();
+        }
+
+        let mut ret = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            ret.push(if current {
+                HistoryEntry::from_line(&line)
+            } else {
+                HistoryEntry {
+                    text: line,
+                    ..Default::default()
+                }
+            });
+        }
+        if !current {
+            ret.reverse();
+        }
+        Ok((ret, current))
+    }
+
+    /// Replaces the contents of the file with the header and the entries.
+    fn write(file: &mut File, entries: &[HistoryEntry]) -> anyhow::Result<()> {
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{HEADER}")?;
+        for entry in entries {
+            writeln!(file, "{}", entry.to_line())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("promkit-history-{}-{}", std::process::id(), name));
+        std::fs::remove_file(&path).ok();
+        path
+    }
+
+    mod append {
+        use super::*;
+
+        #[test]
+        fn test_with_multi_line_entry() {
+            let path = temp_path("multi-line");
+            let file = HistoryFile::new(&path).record_cwd(true);
+            assert!(file.append("select *\nfrom\tt; -- \\n").unwrap());
+
+            let entries = file.load().unwrap();
+            assert_eq!(1, entries.len());
+            assert_eq!("select *\nfrom\tt; -- \\n", entries[0].text);
+            assert!(entries[0].timestamp.is_some());
+            assert_eq!(std::env::current_dir().ok(), entries[0].cwd);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn test_with_policies() {
+            let path = temp_path("policies");
+            let file = HistoryFile::new(&path).ignore_space(true);
+            assert!(file.append("ls").unwrap());
+            assert!(!file.append("ls").unwrap());
+            assert!(!file.append(" secret").unwrap());
+            assert!(!file.append("").unwrap());
+            assert!(file.append("pwd").unwrap());
+            assert!(file.append("ls").unwrap());
+
+            let texts: Vec<_> = file.load().unwrap().into_iter().map(|e| e.text).collect();
+            assert_eq!(vec!["ls", "pwd", "ls"], texts);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn test_with_concurrent_sessions() {
+            let path = temp_path("concurrent");
+            let handles: Vec<_> = (0..4)
+                .map(|session| {
+                    let file = HistoryFile::new(&path);
+                    std::thread::spawn(move || {
+                        for i in 0..25 {
+                            file.append(format!("session {session} entry {i}")).unwrap();
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+
+            let entries = HistoryFile::new(&path).load().unwrap();
+            assert_eq!(100, entries.len());
+            assert!(entries.iter().all(|e| e.text.starts_with("session ")));
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    mod load {
+        use super::*;
+
+        #[test]
+        fn test_with_legacy_lines() {
+            let path = temp_path("legacy");
+            let mut history = History::default();
+            history.insert("echo a\tb");
+            history.insert("echo c");
+            history.insert("123\tfoo\tbar");
+            history.save_to_file(&path).unwrap();
+
+            let file = HistoryFile::new(&path);
+            let texts: Vec<_> = file.load().unwrap().into_iter().map(|e| e.text).collect();
+            assert_eq!(vec!["echo a\tb", "echo c", "123\tfoo\tbar"], texts);
+
+            // Up recalls the most recent entry first.
+            let mut history = file.load_history().unwrap();
+            assert!(history.backward());
+            assert_eq!("123\tfoo\tbar", history.get());
+            assert!(history.backward());
+            assert_eq!("echo c", history.get());
+
+            // The file is converted on the first append.
+            assert!(file.append("echo d").unwrap());
+            let texts: Vec<_> = file.load().unwrap().into_iter().map(|e| e.text).collect();
+            assert_eq!(
+                vec!["echo a\tb", "echo c", "123\tfoo\tbar", "echo d"],
+                texts
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn test_without_file() {
+            let file = HistoryFile::new(temp_path("missing"));
+            assert!(file.load().unwrap().is_empty());
+            assert!(file.load_history().unwrap().get().is_empty());
+        }
+    }
+
+    mod compact {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let path = temp_path("compact");
+            let file = HistoryFile::new(&path).limit_size(2);
+            for text in ["a", "b", "a", "c"] {
+                file.append(text).unwrap();
+            }
+            file.compact().unwrap();
+
+            let texts: Vec<_> = file.load().unwrap().into_iter().map(|e| e.text).collect();
+            assert_eq!(vec!["a", "c"], texts);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
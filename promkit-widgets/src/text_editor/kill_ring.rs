@@ -0,0 +1,110 @@
+use std::{collections::VecDeque, ops::Range};
+
+use promkit_core::grapheme::StyledGraphemes;
+
+/// Stores the text removed by kill operations (e.g. `Ctrl + K`)
+/// so that it can be yanked back, like the kill ring of Emacs.
+///
+/// The most recent kill is yanked first,
+/// and a yank can be replaced with older kills by rotating the ring.
+#[derive(Clone, Default)]
+pub struct KillRing {
+    /// Killed texts, the newest first.
+    entries: VecDeque<String>,
+    /// The index of the entry to be yanked.
+    position: usize,
+
+    /// Optional limit on the number of entries in the ring.
+    /// If set, the oldest entries are dropped to make room for new ones.
+    pub limit_size: Option<usize>,
+
+    /// The range and the resulting text of the last yank,
+    /// used to check that it can still be replaced by a rotation.
+    pub(super) last_yank: Option<(Range<usize>, StyledGraphemes)>,
+}
+
+impl KillRing {
+    /// Adds killed text to the ring. Empty text is ignored.
+    pub fn push<S: Into<String>>(&mut self, text: S) {
+        let text = text.into();
+        if text.is_empty() {
+            return;
+        }
+        self.entries.push_front(text);
+        if let Some(limit) = self.limit_size {
+            self.entries.truncate(limit);
+        }
+        self.position = 0;
+        self.last_yank = None;
+    }
+
+    /// Returns the entry to be yanked, if any.
+    pub fn get(&self) -> Option<&str> {
+        self.entries.get(self.position).map(String::as_str)
+    }
+
+    /// Moves to the next older entry, wrapping around to the newest,
+    /// and returns it.
+    pub fn rotate(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.position = (self.position + 1) % self.entries.len();
+        self.get()
+    }
+
+    /// Moves back to the most recent entry and returns it.
+    pub fn reset(&mut self) -> Option<&str> {
+        self.position = 0;
+        self.get()
+    }
+
+    /// Returns the number of entries in the ring.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing has been killed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod push {
+        use super::*;
+
+        #[test]
+        fn test_with_limit() {
+            let mut ring = KillRing {
+                limit_size: Some(2),
+                ..Default::default()
+            };
+            ring.push("a");
+            ring.push("");
+            ring.push("b");
+            ring.push("c");
+            assert_eq!(2, ring.len());
+            assert_eq!(Some("c"), ring.get());
+        }
+    }
+
+    mod rotate {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut ring = KillRing::default();
+            assert_eq!(None, ring.rotate());
+            ring.push("a");
+            ring.push("b");
+            assert_eq!(Some("a"), ring.rotate());
+            assert_eq!(Some("b"), ring.rotate());
+            ring.rotate();
+            assert_eq!(Some("b"), ring.reset());
+        }
+    }
+}
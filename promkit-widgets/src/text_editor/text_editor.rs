@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
 use promkit_core::grapheme::{StyledGrapheme, StyledGraphemes};
 
@@ -17,13 +17,35 @@ pub enum Mode {
 
 /// A text editor that supports basic editing operations
 /// such as insert, delete, and overwrite.
-/// It utilizes a cursor to navigate and manipulate the text.
+/// It utilizes a cursor to navigate and manipulate the text,
+/// and keeps the history of edits for undo and redo.
 #[derive(Clone)]
-pub struct TextEditor(Cursor<StyledGraphemes>);
+pub struct TextEditor {
+    cursor: Cursor<StyledGraphemes>,
+    /// Snapshots of the text before each edit, the newest last.
+    undo_stack: Vec<Cursor<StyledGraphemes>>,
+    /// Snapshots of the text undone, the newest last.
+    redo_stack: Vec<Cursor<StyledGraphemes>>,
+    /// The kind of the last edit and the cursor position after it,
+    /// used to coalesce consecutive edits into one undo step.
+    last_edit: Option<(Edit, usize)>,
+}
+
+/// The kind of an edit, see [`TextEditor::edit`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// Typing characters; consecutive inserts at the cursor are one undo step.
+    Insert,
+    /// Replacing the whole text (e.g. history or completion);
+    /// consecutive replacements are one undo step.
+    Replace,
+    /// Any other edit, always its own undo step.
+    Other,
+}
 
 impl Default for TextEditor {
     fn default() -> Self {
-        Self(Cursor::new(
+        Self::from_cursor(Cursor::new(
             // Set cursor
             StyledGraphemes::from(" "),
             0,
@@ -34,15 +56,77 @@ impl Default for TextEditor {
 
 impl TextEditor {
     pub fn new<S: AsRef<str>>(s: S) -> Self {
+        Self::from_cursor(Self::cursor_at_tail(s))
+    }
+
+    fn from_cursor(cursor: Cursor<StyledGraphemes>) -> Self {
+        Self {
+            cursor,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    /// Creates the contents for `s` with the cursor at the end.
+    fn cursor_at_tail<S: AsRef<str>>(s: S) -> Cursor<StyledGraphemes> {
         let mut buf = s.as_ref().to_owned();
         buf.push(' ');
-        let pos = buf.len() - 1;
-        Self(Cursor::new(StyledGraphemes::from(buf), pos, false))
+        let graphemes = StyledGraphemes::from(buf);
+        let pos = graphemes.len() - 1;
+        Cursor::new(graphemes, pos, false)
+    }
+
+    /// Applies an edit, recording the previous text for undo
+    /// if the edit changes the text.
+    ///
+    /// An edit of the same kind as the previous one is merged into its undo step
+    /// when the cursor has not moved since then (except for [`Edit::Other`]).
+    fn edit<T, F: FnOnce(&mut Self) -> T>(&mut self, kind: Edit, f: F) -> T {
+        let before = self.cursor.clone();
+        let ret = f(self);
+        if before.contents() == self.cursor.contents() {
+            return ret;
+        }
+
+        let coalesce = kind != Edit::Other && self.last_edit == Some((kind, before.position()));
+        if !coalesce {
+            self.undo_stack.push(before);
+        }
+        self.redo_stack.clear();
+        self.last_edit = Some((kind, self.position()));
+        ret
+    }
+
+    /// Reverts the last edit. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            Some(prev) => {
+                self.redo_stack
+                    .push(std::mem::replace(&mut self.cursor, prev));
+                self.last_edit = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Re-applies the last undone edit. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            Some(next) => {
+                self.undo_stack
+                    .push(std::mem::replace(&mut self.cursor, next));
+                self.last_edit = None;
+                true
+            }
+            None => false,
+        }
     }
 
     /// Returns the current text including the cursor.
     pub fn text(&self) -> StyledGraphemes {
-        self.0.contents().clone()
+        self.cursor.contents().clone()
     }
 
     /// Returns the text without the cursor.
@@ -54,34 +138,76 @@ impl TextEditor {
 
     /// Returns the current position of the cursor within the text.
     pub fn position(&self) -> usize {
-        self.0.position()
+        self.cursor.position()
     }
 
-    /// Masks all characters except the cursor with the specified mask character.
+    /// Masks all graphemes except the cursor with the specified mask character.
     pub fn masking(&self, mask: char) -> StyledGraphemes {
-        self.text()
-            .chars()
-            .into_iter()
+        let text = self.text();
+        text.iter()
             .enumerate()
-            .map(|(i, c)| StyledGrapheme::from(if i == self.text().len() - 1 { c } else { mask }))
+            .map(|(i, g)| {
+                if i == text.len() - 1 {
+                    g.clone()
+                } else {
+                    StyledGrapheme::from(mask)
+                }
+            })
             .collect::<StyledGraphemes>()
     }
 
     /// Replaces the current text with new text and positions the cursor at the end.
     pub fn replace(&mut self, new: &str) {
-        let mut buf = new.to_owned();
-        buf.push(' ');
-        let pos = buf.len() - 1;
-        *self = Self(Cursor::new(StyledGraphemes::from(buf), pos, false));
+        self.edit(Edit::Replace, |editor| {
+            editor.cursor = Self::cursor_at_tail(new);
+        });
     }
 
     /// Inserts a character at the current cursor position.
+    ///
+    /// If the character extends the grapheme before the cursor into a single cluster
+    /// (e.g. a combining mark or a ZWJ-joined emoji), it is merged into that grapheme.
     pub fn insert(&mut self, ch: char) {
+        self.edit(Edit::Insert, |editor| editor.insert_char(ch));
+    }
+
+    fn insert_char(&mut self, ch: char) {
         let pos = self.position();
-        self.0.contents_mut().insert(pos, StyledGrapheme::from(ch));
+        if let Some(prev) = pos
+            .checked_sub(1)
+            .and_then(|i| self.cursor.contents_mut().get_mut(i))
+            && prev.extend(ch)
+        {
+            return;
+        }
+        self.cursor
+            .contents_mut()
+            .insert(pos, StyledGrapheme::from(ch));
         self.forward();
     }
 
+    /// Inserts a string at the current cursor position as a single undo step.
+    pub fn insert_str(&mut self, s: &str) {
+        self.edit(Edit::Other, |editor| {
+            s.chars().for_each(|ch| editor.insert_char(ch))
+        });
+    }
+
+    /// Replaces the graphemes in `range` with `s`
+    /// and moves the cursor to the end of the inserted text.
+    ///
+    /// The range is clamped to the text, excluding the cursor at the end.
+    pub fn replace_range(&mut self, range: Range<usize>, s: &str) {
+        self.edit(Edit::Other, |editor| {
+            let len = editor.cursor.contents().len() - 1;
+            let start = range.start.min(len);
+            let end = range.end.clamp(start, len);
+            editor.cursor.contents_mut().drain(start..end);
+            editor.cursor.move_to(start);
+            s.chars().for_each(|ch| editor.insert_char(ch));
+        });
+    }
+
     pub fn insert_chars(&mut self, vch: &Vec<char>) {
         for ch in vch {
             self.insert(*ch);
@@ -90,14 +216,17 @@ impl TextEditor {
 
     /// Overwrites the character at the current cursor position with the specified character.
     pub fn overwrite(&mut self, ch: char) {
-        if self.0.is_tail() {
+        if self.cursor.is_tail() {
             self.insert(ch)
         } else {
-            let pos = self.position();
-            self.0
-                .contents_mut()
-                .replace_range(pos..pos + 1, ch.to_string());
-            self.forward();
+            self.edit(Edit::Insert, |editor| {
+                let pos = editor.position();
+                editor
+                    .cursor
+                    .contents_mut()
+                    .replace_range(pos..pos + 1, ch.to_string());
+                editor.forward();
+            });
         }
     }
 
@@ -109,118 +238,192 @@ impl TextEditor {
 
     /// Erases the character before the cursor position.
     pub fn erase(&mut self) {
-        if !self.0.is_head() {
-            self.backward();
-            let pos = self.position();
-            self.0.contents_mut().drain(pos..pos + 1);
-        }
-    }
-
-    /// Clears all text and resets the editor to its default state.
-    pub fn erase_all(&mut self) {
-        *self = Self::default();
+        self.edit(Edit::Other, |editor| {
+            if !editor.cursor.is_head() {
+                editor.backward();
+                let pos = editor.position();
+                editor.cursor.contents_mut().drain(pos..pos + 1);
+            }
+        });
+    }
+
+    /// Clears all text and moves the cursor to the beginning.
+    /// Returns the erased text.
+    pub fn erase_all(&mut self) -> String {
+        self.edit(Edit::Other, |editor| {
+            let erased = editor.text_without_cursor().to_string();
+            editor.cursor = Self::cursor_at_tail("");
+            erased
+        })
+    }
+
+    /// Erases the text from the cursor position to the end. Returns the erased text.
+    pub fn erase_to_tail(&mut self) -> String {
+        let tail = self.cursor.contents().len() - 1;
+        self.erase_to_position(tail)
+    }
+
+    /// Erases the text from the beginning to the cursor position. Returns the erased text.
+    pub fn erase_to_head(&mut self) -> String {
+        self.erase_to_position(0)
+    }
+
+    /// Erases the graphemes in `range` and moves the cursor to its start.
+    /// Returns the erased text.
+    ///
+    /// The range is clamped to the text, excluding the cursor at the end.
+    pub fn erase_range(&mut self, range: Range<usize>) -> String {
+        self.edit(Edit::Other, |editor| {
+            let len = editor.cursor.contents().len() - 1;
+            let start = range.start.min(len);
+            let end = range.end.clamp(start, len);
+            let erased = editor
+                .cursor
+                .contents_mut()
+                .drain(start..end)
+                .collect::<StyledGraphemes>()
+                .to_string();
+            editor.cursor.move_to(start);
+            erased
+        })
     }
 
     /// Erases the text from the current cursor position to the specified position,
     /// considering whether pos is greater or smaller than the current position.
-    fn erase_to_position(&mut self, pos: usize) {
+    /// Returns the erased text.
+    fn erase_to_position(&mut self, pos: usize) -> String {
         let current_pos = self.position();
-        if pos > current_pos {
-            self.0.contents_mut().drain(current_pos..pos);
-        } else {
-            self.0.contents_mut().drain(pos..current_pos);
-            self.0.move_to(pos);
-        }
+        self.erase_range(pos.min(current_pos)..pos.max(current_pos))
     }
 
     /// Finds the nearest previous index of any character in `word_break_chars` from the cursor position.
     fn find_previous_nearest_index(&self, word_break_chars: &HashSet<char>) -> usize {
         let current_position = self.position();
         self.text()
-            .chars()
             .iter()
             .enumerate()
             .filter(|&(i, _)| i < current_position.saturating_sub(1))
             .rev()
-            .find(|&(_, c)| word_break_chars.contains(c))
+            .find(|&(_, g)| is_word_break(g, word_break_chars))
             .map(|(i, _)| i + 1)
             .unwrap_or(0)
     }
 
     /// Erases the text from the current cursor position to the nearest previous character in `word_break_chars`.
-    pub fn erase_to_previous_nearest(&mut self, word_break_chars: &HashSet<char>) {
+    /// Returns the erased text.
+    pub fn erase_to_previous_nearest(&mut self, word_break_chars: &HashSet<char>) -> String {
         let pos = self.find_previous_nearest_index(word_break_chars);
-        self.erase_to_position(pos);
+        self.erase_to_position(pos)
     }
 
     /// Moves the cursor to the nearest previous character in `word_break_chars`.
     pub fn move_to_previous_nearest(&mut self, word_break_chars: &HashSet<char>) {
         let pos = self.find_previous_nearest_index(word_break_chars);
-        self.0.move_to(pos);
+        self.cursor.move_to(pos);
     }
 
     /// Finds the nearest next index of any character in `word_break_chars` from the cursor position.
     fn find_next_nearest_index(&self, word_break_chars: &HashSet<char>) -> usize {
         let current_position = self.position();
         self.text()
-            .chars()
             .iter()
             .enumerate()
             .filter(|&(i, _)| i > current_position)
-            .find(|&(_, c)| word_break_chars.contains(c))
+            .find(|&(_, g)| is_word_break(g, word_break_chars))
             .map(|(i, _)| {
-                if i < self.0.contents().len() - 1 {
+                if i < self.cursor.contents().len() - 1 {
                     i + 1
                 } else {
-                    self.0.contents().len() - 1
+                    self.cursor.contents().len() - 1
                 }
             })
-            .unwrap_or(self.0.contents().len() - 1)
+            .unwrap_or(self.cursor.contents().len() - 1)
     }
 
     /// Erases the text from the current cursor position to the nearest next character in `word_break_chars`.
-    pub fn erase_to_next_nearest(&mut self, word_break_chars: &HashSet<char>) {
+    /// Returns the erased text.
+    pub fn erase_to_next_nearest(&mut self, word_break_chars: &HashSet<char>) -> String {
         let pos = self.find_next_nearest_index(word_break_chars);
-        self.erase_to_position(pos);
+        self.erase_to_position(pos)
     }
 
     /// Moves the cursor to the nearest next character in `word_break_chars`.
     pub fn move_to_next_nearest(&mut self, word_break_chars: &HashSet<char>) {
         let pos = self.find_next_nearest_index(word_break_chars);
-        self.0.move_to(pos);
+        self.cursor.move_to(pos);
+    }
+
+    /// Returns the range of the word at the cursor, delimited by `word_break_chars`.
+    /// The range is empty if the cursor is between two delimiters.
+    /// If `escaped`, a delimiter escaped with a backslash, as in `my\ dir`, belongs to the word.
+    pub fn word_range(&self, word_break_chars: &HashSet<char>, escaped: bool) -> Range<usize> {
+        let text = self.text_without_cursor();
+        let position = self.position();
+        let breaks = text
+            .iter()
+            .scan(false, |escaping, g| {
+                let is_escaped = *escaping;
+                *escaping = escaped && !is_escaped && g.as_str() == "\\";
+                Some(!is_escaped && is_word_break(g, word_break_chars))
+            })
+            .collect::<Vec<_>>();
+        let start = breaks
+            .iter()
+            .take(position)
+            .rposition(|is_break| *is_break)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = breaks
+            .iter()
+            .skip(position)
+            .position(|is_break| *is_break)
+            .map(|i| position + i)
+            .unwrap_or(breaks.len());
+        start..end
+    }
+
+    /// Moves the cursor to the specified position, if it is within the text.
+    pub fn move_to(&mut self, position: usize) -> bool {
+        self.cursor.move_to(position)
     }
 
     /// Moves the cursor to the beginning of the text.
     pub fn move_to_head(&mut self) {
-        self.0.move_to_head()
+        self.cursor.move_to_head()
     }
 
     /// Moves the cursor to the end of the text.
     pub fn move_to_tail(&mut self) {
-        self.0.move_to_tail()
+        self.cursor.move_to_tail()
     }
 
     pub fn shift(&mut self, backward: usize, forward: usize) -> bool {
-        self.0.shift(backward, forward)
+        self.cursor.shift(backward, forward)
     }
 
     /// Moves the cursor one position backward, if possible.
     pub fn backward(&mut self) -> bool {
-        self.0.backward()
+        self.cursor.backward()
     }
 
     /// Moves the cursor one position forward, if possible.
     pub fn forward(&mut self) -> bool {
-        self.0.forward()
+        self.cursor.forward()
     }
 }
 
+/// Returns whether the grapheme is a single char contained in `word_break_chars`.
+fn is_word_break(grapheme: &StyledGrapheme, word_break_chars: &HashSet<char>) -> bool {
+    let mut chars = grapheme.as_str().chars();
+    matches!((chars.next(), chars.next()), (Some(ch), None) if word_break_chars.contains(&ch))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     fn new_with_position(s: String, p: usize) -> TextEditor {
-        TextEditor(Cursor::new(StyledGraphemes::from(s), p, false))
+        TextEditor::from_cursor(Cursor::new(StyledGraphemes::from(s), p, false))
     }
 
     mod masking {
@@ -231,6 +434,23 @@ mod test {
             let txt = new_with_position(String::from("abcde "), 0);
             assert_eq!(StyledGraphemes::from("***** "), txt.masking('*'))
         }
+
+        #[test]
+        fn test_with_grapheme_clusters() {
+            let txt = TextEditor::new("e\u{301}👩\u{200d}💻");
+            assert_eq!(StyledGraphemes::from("** "), txt.masking('*'))
+        }
+    }
+
+    mod new {
+        use super::*;
+
+        #[test]
+        fn test_with_grapheme_clusters() {
+            let txt = TextEditor::new("🇯🇵e\u{301}");
+            assert_eq!(3, txt.text().len());
+            assert_eq!(2, txt.position());
+        }
     }
 
     mod erase {
@@ -282,6 +502,14 @@ mod test {
             assert_eq!(StyledGraphemes::from("abc "), txt.text());
             assert_eq!(0, txt.position());
         }
+
+        #[test]
+        fn test_with_grapheme_cluster() {
+            let mut txt = TextEditor::new("a👍🏽");
+            txt.erase();
+            assert_eq!(StyledGraphemes::from("a "), txt.text());
+            assert_eq!(1, txt.position());
+        }
     }
 
     mod find_previous_nearest_index {
@@ -293,7 +521,7 @@ mod test {
         fn test() {
             let mut txt = new_with_position(String::from("koko momo jojo "), 11); // indicate `o`.
             assert_eq!(10, txt.find_previous_nearest_index(&HashSet::from([' '])));
-            txt.0.move_to(10);
+            txt.cursor.move_to(10);
             assert_eq!(5, txt.find_previous_nearest_index(&HashSet::from([' '])));
         }
 
@@ -313,7 +541,7 @@ mod test {
         fn test() {
             let mut txt = new_with_position(String::from("koko momo jojo "), 7); // indicate `m`.
             assert_eq!(10, txt.find_next_nearest_index(&HashSet::from([' '])));
-            txt.0.move_to(10);
+            txt.cursor.move_to(10);
             assert_eq!(14, txt.find_next_nearest_index(&HashSet::from([' '])));
         }
 
@@ -324,6 +552,42 @@ mod test {
         }
     }
 
+    mod word_range {
+        use super::*;
+
+        use std::collections::HashSet;
+
+        #[test]
+        fn test() {
+            let word_break_chars = HashSet::from([' ']);
+            let mut txt = TextEditor::new("git checkout fea");
+            assert_eq!(13..16, txt.word_range(&word_break_chars, false));
+            txt.move_to(5); // indicate `h`.
+            assert_eq!(4..12, txt.word_range(&word_break_chars, false));
+            txt.move_to(4); // indicate `c`.
+            assert_eq!(4..12, txt.word_range(&word_break_chars, false));
+            txt.move_to(3); // indicate ` `.
+            assert_eq!(0..3, txt.word_range(&word_break_chars, false));
+        }
+
+        #[test]
+        fn test_with_escaped_delimiter() {
+            let word_break_chars = HashSet::from([' ']);
+            let txt = TextEditor::new("ls my\\ di");
+            assert_eq!(3..9, txt.word_range(&word_break_chars, true));
+            assert_eq!(7..9, txt.word_range(&word_break_chars, false));
+            // An escaped backslash does not escape the delimiter after it.
+            let txt = TextEditor::new("ls a\\\\ b");
+            assert_eq!(7..8, txt.word_range(&word_break_chars, true));
+        }
+
+        #[test]
+        fn test_between_delimiters() {
+            let txt = TextEditor::new("a  ");
+            assert_eq!(3..3, txt.word_range(&HashSet::from([' ']), false));
+        }
+    }
+
     mod insert {
         use super::*;
 
@@ -383,6 +647,15 @@ mod test {
             assert_eq!(new.text(), txt.text());
             assert_eq!(new.position(), txt.position());
         }
+
+        #[test]
+        fn test_with_combining_mark() {
+            let mut txt = TextEditor::default();
+            txt.insert_chars(&"e\u{301}👩\u{200d}💻".chars().collect());
+            assert_eq!(StyledGraphemes::from("e\u{301}👩\u{200d}💻 "), txt.text());
+            assert_eq!(3, txt.text().len());
+            assert_eq!(2, txt.position());
+        }
     }
 
     mod overwrite {
@@ -657,4 +930,84 @@ mod test {
             assert_eq!(new.position(), txt.position());
         }
     }
+
+    mod undo {
+        use super::*;
+
+        #[test]
+        fn test_coalesces_inserts() {
+            let mut txt = TextEditor::default();
+            txt.insert_chars(&"abc".chars().collect());
+            txt.erase();
+            txt.insert('d');
+            assert_eq!(StyledGraphemes::from("abd "), txt.text());
+
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from("ab "), txt.text());
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from("abc "), txt.text());
+            assert_eq!(3, txt.position());
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from(" "), txt.text());
+            assert!(!txt.undo());
+        }
+
+        #[test]
+        fn test_breaks_on_cursor_move() {
+            let mut txt = TextEditor::new("ac");
+            txt.backward();
+            txt.insert('b');
+            txt.move_to_tail();
+            txt.insert('d');
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from("abc "), txt.text());
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from("ac "), txt.text());
+        }
+
+        #[test]
+        fn test_ignores_no_op() {
+            let mut txt = TextEditor::default();
+            txt.erase();
+            txt.erase_all();
+            assert!(!txt.undo());
+        }
+
+        #[test]
+        fn test_replace() {
+            let mut txt = TextEditor::new("a");
+            txt.replace("history 1");
+            txt.replace("history 2");
+            assert!(txt.undo());
+            assert_eq!(StyledGraphemes::from("a "), txt.text());
+        }
+    }
+
+    mod redo {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let mut txt = TextEditor::default();
+            txt.insert_chars(&"ab".chars().collect());
+            txt.erase_all();
+            assert!(txt.undo());
+            assert!(txt.undo());
+            assert!(txt.redo());
+            assert_eq!(StyledGraphemes::from("ab "), txt.text());
+            assert!(txt.redo());
+            assert_eq!(StyledGraphemes::from(" "), txt.text());
+            assert!(!txt.redo());
+        }
+
+        #[test]
+        fn test_cleared_after_edit() {
+            let mut txt = TextEditor::default();
+            txt.insert('a');
+            txt.undo();
+            txt.insert('b');
+            assert!(!txt.redo());
+            assert_eq!(StyledGraphemes::from("b "), txt.text());
+        }
+    }
 }
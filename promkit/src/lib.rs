@@ -10,17 +10,21 @@ pub mod preset;
 pub mod suggest;
 pub mod validate;
 
-use std::{io, sync::LazyLock};
+#[cfg(test)]
+mod test_util;
+
+use std::sync::LazyLock;
 
 use futures::StreamExt;
-use scopeguard::defer;
 use tokio::sync::Mutex;
 
-use core::crossterm::{
-    cursor,
-    event::{self, Event, EventStream},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode},
+use core::{
+    backend::{Backend, Stdout},
+    crossterm::{
+        cursor,
+        event::{self, Event, EventStream},
+        execute,
+    },
 };
 
 /// Singleton for EventStream. If a new EventStream is created for each Prompt::run,
@@ -74,6 +78,27 @@ pub trait Prompt {
     /// the prompt should terminate its execution.
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal>;
 
+    /// Waits for the work the prompt runs in the background, e.g. filtering a huge list,
+    /// and applies its result.
+    ///
+    /// `run` waits for it along with the next event, so that the prompt keeps
+    /// handling keys while the work is in progress; it must therefore be cancel safe.
+    /// Returns `None` if no work is in progress, which is the default,
+    /// and `Signal::Quit` if the prompt should terminate, like [`Prompt::evaluate`].
+    async fn background(&mut self) -> Option<anyhow::Result<Signal>> {
+        None
+    }
+
+    /// Like [`Prompt::background`], but returns `None` instead of waiting for input
+    /// from outside the prompt, e.g. the next item of a stream that is not ready yet.
+    ///
+    /// `run_with_events` applies the background work with it before each event,
+    /// so that the runs do not depend on timing.
+    /// Defaults to `background`, which suits prompts whose work never waits for input.
+    async fn settle(&mut self) -> Option<anyhow::Result<Signal>> {
+        self.background().await
+    }
+
     /// The type of the result produced by the renderer.
     type Return;
 
@@ -88,6 +113,14 @@ pub trait Prompt {
     /// is defined by the `Return` associated type.
     fn finalize(&mut self) -> anyhow::Result<Self::Return>;
 
+    /// Returns the backend the prompt draws on.
+    ///
+    /// `run` uses it to toggle raw mode and the cursor visibility,
+    /// so it should match the backend given to the renderer. Defaults to stdout.
+    fn output_backend(&self) -> Box<dyn Backend> {
+        Box::new(Stdout)
+    }
+
     /// Runs the prompt, handling events and producing a result.
     ///
     /// This method initializes the terminal, and enters a loop
@@ -98,22 +131,32 @@ pub trait Prompt {
     ///
     /// Returns a `Result` containing the produced result or an error.
     async fn run(&mut self) -> anyhow::Result<Self::Return> {
-        defer! {
-            execute!(
-                io::stdout(),
-                cursor::Show,
-                event::DisableMouseCapture,
-            )
-            .ok();
-            disable_raw_mode().ok();
-        };
-
-        enable_raw_mode()?;
-        execute!(io::stdout(), cursor::Hide)?;
+        let mut backend = scopeguard::guard(self.output_backend(), |mut backend| {
+            execute!(backend, cursor::Show, event::DisableMouseCapture).ok();
+            backend.disable_raw_mode().ok();
+        });
+
+        backend.enable_raw_mode()?;
+        execute!(*backend, cursor::Hide)?;
 
         self.initialize().await?;
 
-        while let Some(event) = EVENT_STREAM.lock().await.next().await {
+        loop {
+            let event = {
+                let mut stream = EVENT_STREAM.lock().await;
+                tokio::select! {
+                    event = stream.next() => event,
+                    Some(ret) = self.background() => {
+                        if ret? == Signal::Quit {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+            };
+            let Some(event) = event else {
+                break;
+            };
             match event {
                 Ok(event) => {
                     // NOTE: For zsh_pretend/tests/resize_roundtrip_wrap_reflow.rs, skipping
@@ -135,4 +178,50 @@ pub trait Prompt {
 
         self.finalize()
     }
+
+    /// Runs the prompt against a scripted sequence of events
+    /// instead of reading them from the terminal.
+    ///
+    /// Combined with the in-memory backend ([`core::backend::Memory`]),
+    /// this allows testing `evaluate` logic and the rendered frame without a terminal.
+    /// The prompt finalizes when a quit signal is received or the events run out.
+    /// The background work is applied before each event until only input
+    /// from outside the prompt is awaited, see [`Prompt::settle`].
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the produced result or an error.
+    async fn run_with_events<I>(&mut self, events: I) -> anyhow::Result<Self::Return>
+    where
+        I: IntoIterator<Item = Event> + Send,
+        I::IntoIter: Send,
+    {
+        let mut backend = scopeguard::guard(self.output_backend(), |mut backend| {
+            execute!(backend, cursor::Show, event::DisableMouseCapture).ok();
+            backend.disable_raw_mode().ok();
+        });
+
+        backend.enable_raw_mode()?;
+        execute!(*backend, cursor::Hide)?;
+
+        self.initialize().await?;
+
+        'events: for event in events {
+            // Skip resize events in the same way as `run`.
+            if event.is_resize() {
+                continue;
+            }
+            // Finish the background work before the event, as a slow typist would.
+            while let Some(ret) = self.settle().await {
+                if ret? == Signal::Quit {
+                    break 'events;
+                }
+            }
+            if self.evaluate(&event).await? == Signal::Quit {
+                break;
+            }
+        }
+
+        self.finalize()
+    }
 }
@@ -14,6 +14,10 @@ pub mod confirm;
 #[cfg_attr(docsrs, doc(cfg(feature = "password")))]
 pub mod password;
 
+#[cfg(feature = "editor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "editor")))]
+pub mod editor;
+
 #[cfg(feature = "json")]
 #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub mod json;
@@ -42,9 +46,32 @@ use std::{future::Future, pin::Pin};
 
 use crate::{core::crossterm::event::Event, Signal};
 
-/// Type alias for the evaluator function used in the prompt.
-pub type Evaluator<T> =
-    for<'a> fn(
-        event: &'a Event,
-        ctx: &'a mut T,
-    ) -> Pin<Box<dyn Future<Output = Result<Signal, anyhow::Error>> + Send + 'a>>;
+/// Type alias for the future returned by an evaluator.
+pub type EvaluateFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<Signal>> + Send + 'a>>;
+
+/// Type alias for the evaluator used in the prompt.
+///
+/// Any `FnMut` closure can be used, so that it can capture its environment
+/// (e.g. a channel sender or a configuration). The preset's `evaluate::default`
+/// can be wrapped as `|event, ctx| Box::pin(evaluate::default(event, ctx))`.
+pub type Evaluator<T> = Box<dyn for<'a> FnMut(&'a Event, &'a mut T) -> EvaluateFuture<'a> + Send>;
+
+/// Calls the evaluator stored in `ctx`.
+///
+/// The evaluator is moved out of `ctx` while it runs,
+/// because it needs a mutable borrow of `ctx` itself.
+// Unused when all the presets are disabled by features.
+#[allow(dead_code)]
+pub(crate) async fn evaluate<T: Send>(
+    ctx: &mut T,
+    evaluator: fn(&mut T) -> &mut Evaluator<T>,
+    event: &Event,
+) -> anyhow::Result<Signal> {
+    let mut current = std::mem::replace(
+        evaluator(ctx),
+        Box::new(|_, _| Box::pin(async { Ok(Signal::Continue) })),
+    );
+    let ret = current(event, ctx).await;
+    *evaluator(ctx) = current;
+    ret
+}
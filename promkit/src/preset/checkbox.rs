@@ -4,15 +4,16 @@ use std::fmt::Display;
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::{
         checkbox::{self, config::Config},
         text::{self, Text},
@@ -36,6 +37,8 @@ pub struct Checkbox {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the title displayed above the checkbox list.
     pub title: text::State,
     /// State for the checkbox list itself.
@@ -45,9 +48,10 @@ pub struct Checkbox {
 #[async_trait::async_trait]
 impl crate::Prompt for Checkbox {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 [
                     (Index::Title, self.title.create_graphemes(size.0, size.1)),
                     (
@@ -55,6 +59,7 @@ impl crate::Prompt for Checkbox {
                         self.checkbox.create_graphemes(size.0, size.1),
                     ),
                 ],
+                [(Index::Checkbox, PaneConstraint::default().priority(1))],
                 true,
             )
             .await?,
@@ -63,8 +68,8 @@ impl crate::Prompt for Checkbox {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -80,13 +85,18 @@ impl crate::Prompt for Checkbox {
             .map(|e| e.to_string())
             .collect())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Checkbox {
     fn new_with_checkbox(checkbox: checkbox::Checkbox) -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             title: text::State {
                 config: text::config::Config {
                     style: Some(ContentStyle {
@@ -167,8 +177,17 @@ impl Checkbox {
     }
 
     /// Sets the evaluator function for handling input events.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
         self
     }
 
@@ -191,3 +210,42 @@ impl Checkbox {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{backend::Memory, crossterm::event::KeyCode},
+        test_util::key,
+        Prompt,
+    };
+
+    mod run_with_events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let memory = Memory::new(20, 10);
+            let mut checkbox = Checkbox::new(["a", "b", "c"])
+                .title("Pick")
+                .backend(memory.clone());
+
+            let mut ret = checkbox
+                .run_with_events([
+                    key(KeyCode::Char(' ')),
+                    key(KeyCode::Down),
+                    key(KeyCode::Down),
+                    key(KeyCode::Char(' ')),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            ret.sort();
+            assert_eq!(vec!["a", "c"], ret);
+            // The list is scrolled so that it starts from the cursor.
+            assert_eq!(vec!["Pick", "❯ ☒ c", "", ""], memory.lines()[..4].to_vec());
+        }
+    }
+}
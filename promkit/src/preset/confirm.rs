@@ -1,6 +1,6 @@
 //! Contains a simple yes/no confirmation prompt.
 
-use crate::Prompt;
+use crate::{core::backend::Backend, Prompt};
 
 use crate::preset::readline::Readline;
 
@@ -20,6 +20,12 @@ impl Confirm {
         )
     }
 
+    /// Sets the backend to draw the confirmation prompt on.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self = Confirm(self.0.backend(backend));
+        self
+    }
+
     /// Sets the title text displayed above the confirmation prompt.
     pub async fn run(&mut self) -> anyhow::Result<String> {
         self.0.run().await
@@ -0,0 +1,274 @@
+//! Offers a multi-line text editor, e.g. for commit messages or SQL queries.
+
+use std::collections::HashSet;
+
+use crate::{
+    core::{
+        backend::{Backend, Stdout},
+        crossterm::{
+            event::Event,
+            style::{Attribute, Attributes, Color, ContentStyle},
+        },
+        render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
+        Widget,
+    },
+    preset::{self, EvaluateFuture, Evaluator},
+    widgets::{
+        editor,
+        text::{self, Text},
+        text_editor,
+    },
+    Signal,
+};
+
+pub mod evaluate;
+
+/// Represents the indices of various components in the editor preset.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Index {
+    Title = 0,
+    Editor = 1,
+}
+
+/// `Editor` struct provides a prompt for entering multiple lines of text.
+/// <kbd>Enter</kbd> inserts a line break,
+/// and <kbd>Alt + Enter</kbd> or <kbd>Ctrl + D</kbd> submits the text.
+pub struct Editor {
+    /// Shared renderer for the prompt, allowing for rendering of UI components.
+    pub renderer: Option<SharedRenderer<Index>>,
+    /// Function to evaluate the input events and update the state of the prompt.
+    pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
+    /// Holds a title's renderer state, used for rendering the title section.
+    pub title: text::State,
+    /// Holds an editor's renderer state, used for rendering the text input area.
+    pub editor: editor::State,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            renderer: None,
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
+            title: text::State {
+                config: text::config::Config {
+                    style: Some(ContentStyle {
+                        attributes: Attributes::from(Attribute::Bold),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            editor: editor::State {
+                editor: Default::default(),
+                config: editor::Config {
+                    active_char_style: ContentStyle {
+                        background_color: Some(Color::DarkCyan),
+                        ..Default::default()
+                    },
+                    inactive_char_style: ContentStyle::default(),
+                    line_numbers: false,
+                    line_number_style: ContentStyle {
+                        foreground_color: Some(Color::DarkGrey),
+                        ..Default::default()
+                    },
+                    edit_mode: Default::default(),
+                    word_break_chars: HashSet::from([' ']),
+                    lines: Default::default(),
+                },
+            },
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Prompt for Editor {
+    async fn initialize(&mut self) -> anyhow::Result<()> {
+        let size = self.backend.size()?;
+        self.renderer = Some(SharedRenderer::new(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
+                [
+                    (Index::Title, self.title.create_graphemes(size.0, size.1)),
+                    (Index::Editor, self.editor.create_graphemes(size.0, size.1)),
+                ],
+                [(Index::Editor, PaneConstraint::default().priority(1))],
+                true,
+            )
+            .await?,
+        ));
+        Ok(())
+    }
+
+    async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
+        self.render(size.0, size.1).await?;
+        ret
+    }
+
+    type Return = String;
+
+    fn finalize(&mut self) -> anyhow::Result<Self::Return> {
+        let ret = self.editor.editor.text_without_cursor().to_string();
+
+        // Reset the editor state for the next prompt.
+        self.editor.editor = Default::default();
+
+        Ok(ret)
+    }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
+}
+
+impl Editor {
+    /// Sets the title text displayed above the editor.
+    pub fn title<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.title.text = Text::from(text);
+        self
+    }
+
+    /// Sets the style for the title text.
+    pub fn title_style(mut self, style: ContentStyle) -> Self {
+        self.title.config.style = Some(style);
+        self
+    }
+
+    /// Sets the initial text, with the cursor at the end.
+    pub fn text<T: AsRef<str>>(mut self, text: T) -> Self {
+        self.editor.editor = editor::Editor::new(text);
+        self
+    }
+
+    /// Shows the line numbers in a gutter on the left.
+    pub fn line_numbers(mut self, enabled: bool) -> Self {
+        self.editor.config.line_numbers = enabled;
+        self
+    }
+
+    /// Sets the style for the line numbers.
+    pub fn line_number_style(mut self, style: ContentStyle) -> Self {
+        self.editor.config.line_number_style = style;
+        self
+    }
+
+    /// Sets the style for the currently active character in the editor.
+    pub fn active_char_style(mut self, style: ContentStyle) -> Self {
+        self.editor.config.active_char_style = style;
+        self
+    }
+
+    /// Sets the style for characters that are not currently active in the editor.
+    pub fn inactive_char_style(mut self, style: ContentStyle) -> Self {
+        self.editor.config.inactive_char_style = style;
+        self
+    }
+
+    /// Sets the edit mode for the editor, either insert or overwrite.
+    pub fn edit_mode(mut self, mode: text_editor::Mode) -> Self {
+        self.editor.config.edit_mode = mode;
+        self
+    }
+
+    /// Sets the characters to be for word break.
+    pub fn word_break_chars(mut self, characters: HashSet<char>) -> Self {
+        self.editor.config.word_break_chars = characters;
+        self
+    }
+
+    /// Sets the number of lines available for rendering the editor;
+    /// the view scrolls to follow the cursor beyond them.
+    pub fn lines(mut self, lines: usize) -> Self {
+        self.editor.config.lines = Some(lines);
+        self
+    }
+
+    /// Sets the function to evaluate the input, allowing for custom evaluation logic.
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
+        self
+    }
+
+    /// Render the prompt with the specified width and height.
+    async fn render(&mut self, width: u16, height: u16) -> anyhow::Result<()> {
+        match self.renderer.as_ref() {
+            Some(renderer) => {
+                renderer
+                    .update([
+                        (Index::Title, self.title.create_graphemes(width, height)),
+                        (Index::Editor, self.editor.create_graphemes(width, height)),
+                    ])
+                    .render()
+                    .await
+            }
+            None => Err(anyhow::anyhow!("Renderer not initialized")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{
+            backend::Memory,
+            crossterm::event::{KeyCode, KeyModifiers},
+        },
+        test_util::{key, key_with, type_str},
+        Prompt,
+    };
+
+    mod run_with_events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let memory = Memory::new(20, 5);
+            let mut editor = Editor::default()
+                .title("Message")
+                .line_numbers(true)
+                .backend(memory.clone());
+
+            let mut events = type_str("fix bug\n\ndetails");
+            events.extend([key(KeyCode::Up), key(KeyCode::Up), key(KeyCode::Char('!'))]);
+            events.push(key_with(KeyCode::Enter, KeyModifiers::ALT));
+
+            assert_eq!(
+                "fix bug!\n\ndetails",
+                editor.run_with_events(events).await.unwrap()
+            );
+            assert_eq!(
+                vec!["Message", "1 fix bug!", "2", "3 details", ""],
+                memory.lines()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_scroll() {
+            let memory = Memory::new(20, 5);
+            let mut editor = Editor::default().lines(2).backend(memory.clone());
+
+            let mut events = type_str("a\nb\nc");
+            events.push(key_with(KeyCode::Char('d'), KeyModifiers::CONTROL));
+
+            assert_eq!("a\nb\nc", editor.run_with_events(events).await.unwrap());
+            assert_eq!(vec!["b", "c", "", "", ""], memory.lines());
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use promkit_widgets::text_editor;
+
+use crate::{
+    core::crossterm::{
+        event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+        style::ContentStyle,
+    },
+    preset::editor::Editor,
+    Signal,
+};
+
+/// Default key bindings for the editor.
+///
+/// | Key                       | Action
+/// | :------------------------ | :-------------------------------------------
+/// | <kbd>Alt + Enter</kbd>    | Exit the editor with the text (also <kbd>Ctrl + D</kbd>)
+/// | <kbd>Enter</kbd>          | Insert a line break
+/// | <kbd>Ctrl + C</kbd>       | Interrupt the current operation
+/// | <kbd>←</kbd>              | Move the cursor one character to the left
+/// | <kbd>→</kbd>              | Move the cursor one character to the right
+/// | <kbd>↑</kbd>              | Move the cursor to the previous line
+/// | <kbd>↓</kbd>              | Move the cursor to the next line
+/// | <kbd>Ctrl + A</kbd>       | Move the cursor to the start of the line
+/// | <kbd>Ctrl + E</kbd>       | Move the cursor to the end of the line
+/// | <kbd>Alt + <</kbd>        | Move the cursor to the start of the text
+/// | <kbd>Alt + ></kbd>        | Move the cursor to the end of the text
+/// | <kbd>Backspace</kbd>      | Delete the character before the cursor
+/// | <kbd>Ctrl + K</kbd>       | Delete from the cursor to the end of the line
+/// | <kbd>Ctrl + W</kbd>       | Erase to the previous nearest character within set (default: whitespace)
+/// | <kbd>Ctrl + Z</kbd>       | Undo the last edit (also <kbd>Ctrl + _</kbd>)
+/// | <kbd>Alt + _</kbd>        | Redo the last undone edit
+pub async fn default(event: &Event, ctx: &mut Editor) -> anyhow::Result<Signal> {
+    let editor = &mut ctx.editor.editor;
+    match event {
+        // Render for refreshing prompt on resize.
+        Event::Resize(width, height) => {
+            ctx.render(*width, *height).await?;
+        }
+
+        // Submit the text.
+        Event::Key(KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('d'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            // For representing the end of the prompt,
+            // reset the style of the cursor to default.
+            ctx.editor.config.active_char_style = ContentStyle::default();
+            return Ok(Signal::Quit);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => return Err(anyhow::anyhow!("ctrl+c")),
+
+        Event::Key(KeyEvent {
+            code: KeyCode::Enter,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.insert_newline(),
+
+        // Move cursor.
+        Event::Key(KeyEvent {
+            code: KeyCode::Left,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.backward();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Right,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.forward();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Up,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.up();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Down,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.down();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('a'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.move_to_line_head(),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('e'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.move_to_line_tail(),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('<'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.move_to_head(),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('>'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.move_to_tail(),
+
+        // Erase char(s).
+        Event::Key(KeyEvent {
+            code: KeyCode::Backspace,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => editor.erase(),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('k'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.erase_to_line_tail();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('w'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.erase_to_previous_nearest(&ctx.editor.config.word_break_chars);
+        }
+
+        // Undo / redo.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        // Terminals send Ctrl + _ as 0x1F, which is read as Ctrl + 7.
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('7'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.undo();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            editor.redo();
+        }
+
+        // Input char.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::SHIFT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => match ctx.editor.config.edit_mode {
+            text_editor::Mode::Insert => editor.insert(*ch),
+            text_editor::Mode::Overwrite => editor.overwrite(*ch),
+        },
+
+        _ => (),
+    }
+    Ok(Signal::Continue)
+}
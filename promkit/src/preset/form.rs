@@ -2,16 +2,18 @@
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
-            style::{Attribute, Attributes, ContentStyle},
+            style::{Attribute, Attributes, Color, ContentStyle},
         },
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
-    widgets::{cursor::Cursor, text_editor},
+    preset::{self, EvaluateFuture, Evaluator},
+    validate::{Rule, ValidatorManager},
+    widgets::{cursor::Cursor, text, text_editor},
     Signal,
 };
 
@@ -33,12 +35,18 @@ pub struct Form {
     pub renderer: Option<SharedRenderer<usize>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the multiple text editor components.
     pub readlines: Cursor<Vec<text_editor::State>>,
     /// Default styles applied to text editors.
     pub focus_styles: Vec<Style>,
     /// Styles applied to text editors when they are unselected.
     pub unfocus_styles: Vec<Style>,
+    /// Optional validators for each text editor, checked when the form is submitted.
+    pub validators: Vec<Option<ValidatorManager<str>>>,
+    /// Holds an error message's renderer state, rendered below the text editors.
+    pub error_message: text::State,
 }
 
 #[async_trait::async_trait]
@@ -47,14 +55,20 @@ impl crate::Prompt for Form {
         // Update styles based on the current position.
         self.overwrite_styles();
 
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 self.readlines
                     .contents()
                     .iter()
                     .enumerate()
-                    .map(|(i, state)| (i, state.create_graphemes(size.0, size.1))),
+                    .map(|(i, state)| (i, state.create_graphemes(size.0, size.1)))
+                    .chain([(
+                        self.error_message_index(),
+                        self.error_message.create_graphemes(size.0, size.1),
+                    )]),
+                self.constraints(),
                 true,
             )
             .await?,
@@ -63,12 +77,12 @@ impl crate::Prompt for Form {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
 
         // Update the styles based on the current position.
         self.overwrite_styles();
 
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -83,6 +97,10 @@ impl crate::Prompt for Form {
             .map(|state| state.texteditor.text_without_cursor().to_string())
             .collect())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Form {
@@ -123,11 +141,107 @@ impl Form {
 
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
+            validators: readlines.iter().map(|_| None).collect(),
             readlines: Cursor::new(readlines, 0, false),
             focus_styles,
             unfocus_styles,
+            error_message: text::State {
+                text: Default::default(),
+                config: text::config::Config {
+                    style: Some(ContentStyle {
+                        foreground_color: Some(Color::DarkRed),
+                        attributes: Attributes::from(Attribute::Bold),
+                        ..Default::default()
+                    }),
+                    lines: None,
+                },
+            },
+        }
+    }
+
+    /// Configures a validator for the text editor at `index`
+    /// with a function to validate the input and another to configure the error message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no text editor at `index`.
+    pub fn validator<V, E>(self, index: usize, validator: V, error_message_generator: E) -> Self
+    where
+        V: Fn(&str) -> bool + Send + Sync + 'static,
+        E: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.set_validator(
+            index,
+            ValidatorManager::new(validator, error_message_generator),
+        )
+    }
+
+    /// Configures a validation rule for the text editor at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no text editor at `index`.
+    pub fn rule(self, index: usize, rule: Rule) -> Self {
+        self.set_validator(index, rule.into())
+    }
+
+    fn set_validator(mut self, index: usize, validator: ValidatorManager<str>) -> Self {
+        let len = self.validators.len();
+        match self.validators.get_mut(index) {
+            Some(slot) => *slot = Some(validator),
+            None => panic!("no text editor at index {index} in a form of {len}"),
         }
+        self
+    }
+
+    /// Validates all text editors, returning the index and error message of the first invalid one.
+    fn validate(&self) -> Option<(usize, String)> {
+        self.readlines
+            .contents()
+            .iter()
+            .zip(&self.validators)
+            .enumerate()
+            .find_map(|(i, (state, validator))| {
+                let text = state.texteditor.text_without_cursor().to_string();
+                validator
+                    .as_ref()
+                    .filter(|validator| !validator.validate(&text))
+                    .map(|validator| (i, validator.generate_error_message(&text)))
+            })
+    }
+
+    /// Gives the focused text editor the highest priority,
+    /// so that it stays shown when the terminal is too short for all the panes.
+    fn constraints(&self) -> Vec<(usize, PaneConstraint)> {
+        let position = self.readlines.position();
+        (0..self.readlines.contents().len())
+            .map(|i| {
+                let priority = if i == position { 1 } else { 0 };
+                (i, PaneConstraint::default().priority(priority))
+            })
+            .collect()
+    }
+
+    /// The renderer index of the error message, placed after all text editors.
+    fn error_message_index(&self) -> usize {
+        self.readlines.contents().len()
+    }
+
+    /// Sets the evaluator function for handling input events.
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
+        self
     }
 
     /// Render the prompt with the specified width and height.
@@ -135,12 +249,17 @@ impl Form {
         match self.renderer.as_ref() {
             Some(renderer) => {
                 renderer
+                    .constrain(self.constraints())
                     .update(
                         self.readlines
                             .contents()
                             .iter()
                             .enumerate()
-                            .map(|(i, state)| (i, state.create_graphemes(width, height))),
+                            .map(|(i, state)| (i, state.create_graphemes(width, height)))
+                            .chain([(
+                                self.error_message_index(),
+                                self.error_message.create_graphemes(width, height),
+                            )]),
                     )
                     .render()
                     .await
@@ -169,3 +288,87 @@ impl Form {
             });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{backend::Memory, crossterm::event::KeyCode},
+        test_util::key,
+        Prompt,
+    };
+
+    mod validate {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_focuses_first_invalid_field() {
+            let memory = Memory::new(40, 5);
+            let mut form =
+                Form::new([text_editor::State::default(), text_editor::State::default()])
+                    .rule(1, Rule::non_empty().message("Name is required"))
+                    .backend(memory.clone());
+
+            let ret = form
+                .run_with_events([
+                    key(KeyCode::Char('a')),
+                    key(KeyCode::Enter),
+                    // The cursor moved to the second field.
+                    key(KeyCode::Char('b')),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!(vec!["a", "b"], ret);
+            assert!(memory.lines().iter().any(|line| line == "Name is required"));
+        }
+
+        #[tokio::test]
+        async fn test_quits_when_valid() {
+            let mut form = Form::new([text_editor::State::default()])
+                .validator(0, |text| text.len() == 1, |_| String::from("invalid"))
+                .backend(Memory::new(40, 5));
+
+            let ret = form
+                .run_with_events([
+                    key(KeyCode::Char('a')),
+                    key(KeyCode::Enter),
+                    key(KeyCode::Char('b')),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!(vec!["a"], ret);
+        }
+    }
+
+    mod render {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_keeps_focused_field_shown() {
+            let memory = Memory::new(40, 1);
+            let mut form =
+                Form::new([text_editor::State::default(), text_editor::State::default()])
+                    .backend(memory.clone());
+
+            form.run_with_events([key(KeyCode::Down), key(KeyCode::Char('b'))])
+                .await
+                .unwrap();
+
+            assert_eq!(vec!["b"], memory.lines());
+        }
+    }
+
+    mod rule {
+        use super::*;
+
+        #[test]
+        #[should_panic(expected = "no text editor at index 2 in a form of 2")]
+        fn test_with_out_of_range_index() {
+            let _ = Form::new([text_editor::State::default(), text_editor::State::default()])
+                .rule(2, Rule::non_empty());
+        }
+    }
+}
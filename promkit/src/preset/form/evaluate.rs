@@ -1,4 +1,4 @@
-use promkit_widgets::text_editor;
+use promkit_widgets::{text::Text, text_editor};
 
 use crate::{
     core::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
@@ -16,13 +16,23 @@ pub async fn default(event: &Event, ctx: &mut Form) -> anyhow::Result<Signal> {
             ctx.render(*width, *height).await?;
         }
 
-        // Quit
+        // Quit when all fields are valid,
+        // otherwise focus the first invalid field and show its error message.
         Event::Key(KeyEvent {
             code: KeyCode::Enter,
             modifiers: KeyModifiers::NONE,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => return Ok(Signal::Quit),
+        }) => match ctx.validate() {
+            Some((index, message)) => {
+                ctx.readlines.move_to(index);
+                ctx.error_message.text = Text::from(message);
+            }
+            None => {
+                ctx.error_message.text = Text::default();
+                return Ok(Signal::Quit);
+            }
+        },
         Event::Key(KeyEvent {
             code: KeyCode::Char('c'),
             modifiers: KeyModifiers::CONTROL,
@@ -113,9 +123,7 @@ pub async fn default(event: &Event, ctx: &mut Form) -> anyhow::Result<Signal> {
             modifiers: KeyModifiers::CONTROL,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => ctx.readlines.contents_mut()[current_position]
-            .texteditor
-            .erase_all(),
+        }) => ctx.readlines.contents_mut()[current_position].kill_all(),
 
         // Erase to the nearest character.
         Event::Key(KeyEvent {
@@ -123,29 +131,46 @@ pub async fn default(event: &Event, ctx: &mut Form) -> anyhow::Result<Signal> {
             modifiers: KeyModifiers::CONTROL,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
+        }) => ctx.readlines.contents_mut()[current_position].kill_to_previous_nearest(),
+
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('d'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => ctx.readlines.contents_mut()[current_position].kill_to_next_nearest(),
+
+        // Undo / redo.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        // Terminals send Ctrl + _ as 0x1F, which is read as Ctrl + 7.
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('7'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
         }) => {
-            let word_break_chars = ctx.readlines.contents_mut()[current_position]
-                .config
-                .word_break_chars
-                .clone();
             ctx.readlines.contents_mut()[current_position]
-                .texteditor
-                .erase_to_previous_nearest(&word_break_chars)
+                .texteditor.undo();
         }
-
         Event::Key(KeyEvent {
-            code: KeyCode::Char('d'),
+            code: KeyCode::Char('_'),
             modifiers: KeyModifiers::ALT,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }) => {
-            let word_break_chars = ctx.readlines.contents_mut()[current_position]
-                .config
-                .word_break_chars
-                .clone();
             ctx.readlines.contents_mut()[current_position]
-                .texteditor
-                .erase_to_next_nearest(&word_break_chars)
+                .texteditor.redo();
         }
 
         Event::Key(KeyEvent {
@@ -2,15 +2,16 @@
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::{
         jsonstream::{
             self,
@@ -37,6 +38,8 @@ pub struct Json {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the title text.
     pub title: text::State,
     /// State for the JSON data, including formatting and rendering options.
@@ -46,13 +49,15 @@ pub struct Json {
 #[async_trait::async_trait]
 impl crate::Prompt for Json {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 [
                     (Index::Title, self.title.create_graphemes(size.0, size.1)),
                     (Index::Json, self.json.create_graphemes(size.0, size.1)),
                 ],
+                [(Index::Json, PaneConstraint::default().priority(1))],
                 true,
             )
             .await?,
@@ -61,8 +66,8 @@ impl crate::Prompt for Json {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -72,6 +77,10 @@ impl crate::Prompt for Json {
     fn finalize(&mut self) -> anyhow::Result<Self::Return> {
         Ok(())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Json {
@@ -79,7 +88,8 @@ impl Json {
     pub fn new(stream: JsonStream) -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             title: text::State {
                 config: text::config::Config {
                     style: Some(ContentStyle {
@@ -168,8 +178,17 @@ impl Json {
     }
 
     /// Sets the evaluator function for handling events in the JSON preset.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
         self
     }
 
@@ -4,15 +4,16 @@ use std::fmt::Display;
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::{
         listbox::{self, config::Config},
         text::{self, Text},
@@ -35,6 +36,8 @@ pub struct Listbox {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the title displayed above the selectable list.
     pub title: text::State,
     /// State for the selectable list itself.
@@ -44,9 +47,10 @@ pub struct Listbox {
 #[async_trait::async_trait]
 impl crate::Prompt for Listbox {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 [
                     (Index::Title, self.title.create_graphemes(size.0, size.1)),
                     (
@@ -54,6 +58,7 @@ impl crate::Prompt for Listbox {
                         self.listbox.create_graphemes(size.0, size.1),
                     ),
                 ],
+                [(Index::Listbox, PaneConstraint::default().priority(1))],
                 true,
             )
             .await?,
@@ -62,8 +67,8 @@ impl crate::Prompt for Listbox {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -73,6 +78,10 @@ impl crate::Prompt for Listbox {
     fn finalize(&mut self) -> anyhow::Result<Self::Return> {
         Ok(self.listbox.listbox.get().to_string())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Listbox {
@@ -86,7 +95,8 @@ impl Listbox {
     pub fn new<T: Display, I: IntoIterator<Item = T>>(items: I) -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             title: text::State {
                 config: text::config::Config {
                     style: Some(ContentStyle {
@@ -149,8 +159,17 @@ impl Listbox {
     }
 
     /// Sets the evaluator function for handling input events.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
         self
     }
 
@@ -1,8 +1,8 @@
 //! Provides a password input interface with masking and validation.
 
 use crate::{
-    core::crossterm::style::ContentStyle,
-    validate::{ErrorMessageGenerator, Validator},
+    core::{backend::Backend, crossterm::style::ContentStyle},
+    validate::{Rule, ValidateFuture},
     Prompt,
 };
 
@@ -56,15 +56,37 @@ impl Password {
     }
 
     /// Configures a validator for the password input with a function to validate the input and another to configure the error message.
-    pub fn validator(
-        mut self,
-        validator: Validator<str>,
-        error_message_generator: ErrorMessageGenerator<str>,
-    ) -> Self {
+    pub fn validator<V, E>(mut self, validator: V, error_message_generator: E) -> Self
+    where
+        V: Fn(&str) -> bool + Send + Sync + 'static,
+        E: Fn(&str) -> String + Send + Sync + 'static,
+    {
         self = Password(self.0.validator(validator, error_message_generator));
         self
     }
 
+    /// Configures a validation rule for the password input.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self = Password(self.0.rule(rule));
+        self
+    }
+
+    /// Configures an asynchronous validator for the password input.
+    pub fn async_validator<V, E>(mut self, validator: V, error_message_generator: E) -> Self
+    where
+        V: for<'a> Fn(&'a str) -> ValidateFuture<'a> + Send + Sync + 'static,
+        E: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self = Password(self.0.async_validator(validator, error_message_generator));
+        self
+    }
+
+    /// Sets the backend to draw the password prompt on.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self = Password(self.0.backend(backend));
+        self
+    }
+
     /// Runs the password prompt, allowing the user to input a password.
     pub async fn run(&mut self) -> anyhow::Result<String> {
         self.0.run().await
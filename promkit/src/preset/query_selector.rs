@@ -1,18 +1,26 @@
 //! Facilitates querying and selecting from a set of options in a structured format.
 
-use std::fmt::Display;
+use std::{fmt::Display, sync::Arc};
+
+use futures::{
+    stream::{BoxStream, Stream},
+    FutureExt, StreamExt,
+};
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
+        grapheme::StyledGraphemes,
+        layout::{self, Layout, Size},
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::{
         listbox::{self, Listbox},
         text::{self, Text},
@@ -22,19 +30,44 @@ use crate::{
 };
 
 pub mod evaluate;
+pub mod fuzzy;
+pub mod multi;
+pub use multi::MultiQuerySelector;
+mod preview;
+use preview::PreviewJob;
+pub use preview::{Preview, PreviewPosition};
+mod store;
+pub use store::ItemStore;
 
 /// Represents the indices of various components in the query selector preset.
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub enum Index {
     Title = 0,
     Readline = 1,
-    List = 2,
+    Status = 2,
+    List = 3,
+    Preview = 4,
 }
 
+/// Maximum number of items added from the source at once.
+const SOURCE_BATCH_SIZE: usize = 8192;
+
 /// Used to process and filter a list of options
 /// based on the input text in the `QuerySelector` component.
+/// It is given all the options to match, and returns the kept ones
+/// in the order to display them. Returning an item that is not among
+/// the given ones fails the search.
 pub type Filter = fn(&str, &Vec<String>) -> Vec<String>;
 
+/// Decides which items are listed for the input, and in which order.
+#[derive(Clone, Copy)]
+pub enum Matcher {
+    /// Applies the filter to all the items at once.
+    Filter(Filter),
+    /// Ranks the items with the fuzzy matcher in parallel, see [`fuzzy`].
+    Fuzzy,
+}
+
 /// Represents a query selection component that combines a text editor
 /// for input and a list box
 /// for displaying filtered options based on the input.
@@ -43,32 +76,128 @@ pub struct QuerySelector {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the title displayed above the query selection.
     pub title: text::State,
     /// State for the text editor component.
     pub readline: text_editor::State,
-    /// Initial state for the list box component.
-    pub init_list: Listbox,
-    /// State for the list box component.
+    /// The items and the matches for the input.
+    pub store: ItemStore,
+    /// State for the list box component,
+    /// holding the visible part of the matches around the selected one.
+    /// It is rebuilt on each render; moving its selection by an item,
+    /// e.g. with `list.listbox.forward()`, moves the selection in the matches.
     pub list: listbox::State,
-    /// A filter function to apply to the list box items
-    /// based on the text editor input.
+    /// Position of the selected item in the matches.
+    position: usize,
+    /// Position in the matches of the item selected in `list` when it was built.
+    list_selected: usize,
+    /// A filter function to apply to the items
+    /// based on the text editor input, unless `fuzzy`.
     pub filter: Filter,
+    /// Whether to rank the items with the fuzzy matcher instead of `filter`.
+    pub fuzzy: bool,
+    /// Whether `filter` only drops more items as the input grows,
+    /// so that an extended input is matched against the previous matches only.
+    pub narrowing: bool,
+    /// Optional style for the characters of the items matching the input,
+    /// as found by [`fuzzy::find`].
+    pub highlight_style: Option<ContentStyle>,
+    /// Stream the items are being loaded from, if any.
+    pub source: Option<BoxStream<'static, String>>,
+    /// State for the status line showing the number of matched and all items.
+    pub status: text::State,
+    /// Whether to show the status line.
+    pub show_status: bool,
+    /// The picked items and their marks in multi-select mode.
+    marks: Option<multi::Marks>,
+    /// Produces the preview of the selected item, if any.
+    pub previewer: Option<Arc<dyn Preview>>,
+    /// State for the preview pane. Below the list,
+    /// it takes half of the terminal unless its lines are set.
+    pub preview: text::State,
+    /// Where the preview pane is placed relative to the list.
+    pub preview_position: PreviewPosition,
+    /// The item the preview is for.
+    previewed: Option<String>,
+    /// The preview in progress.
+    preview_job: Option<PreviewJob>,
 }
 
 #[async_trait::async_trait]
 impl crate::Prompt for QuerySelector {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        self.init_renderer().await
+    }
+
+    async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
+        // Store the previous text in the readline before evaluating the event.
+        let prev = self.readline.texteditor.text_without_cursor().to_string();
+
+        // Evaluate the event using the provided evaluator function.
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+
+        self.update(&prev, matches!(ret, Ok(Signal::Quit))).await?;
+        ret
+    }
+
+    async fn background(&mut self) -> Option<anyhow::Result<Signal>> {
+        self.load(true)
+            .await
+            .map(|ret| ret.map(|()| Signal::Continue))
+    }
+
+    async fn settle(&mut self) -> Option<anyhow::Result<Signal>> {
+        self.load(false)
+            .await
+            .map(|ret| ret.map(|()| Signal::Continue))
+    }
+
+    type Return = String;
+
+    fn finalize(&mut self) -> anyhow::Result<Self::Return> {
+        Ok(self.selected().unwrap_or_default().to_string())
+    }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
+}
+
+/// The background work of the prompt, see [`QuerySelector::load`].
+enum Work {
+    Searched(anyhow::Result<()>),
+    Loaded(Option<String>),
+    Previewed(Vec<StyledGraphemes>),
+}
+
+async fn next_item(source: &mut Option<BoxStream<'static, String>>) -> Option<String> {
+    match source {
+        Some(source) => source.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn wait_preview(job: &mut Option<PreviewJob>) -> Vec<StyledGraphemes> {
+    match job {
+        Some(job) => job.wait().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The parts of the prompt shared with [`MultiQuerySelector`].
+impl QuerySelector {
+    async fn init_renderer(&mut self) -> anyhow::Result<()> {
+        self.request_preview();
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
+                self.panes(size.0, size.1),
                 [
-                    (Index::Title, self.title.create_graphemes(size.0, size.1)),
-                    (
-                        Index::Readline,
-                        self.readline.create_graphemes(size.0, size.1),
-                    ),
-                    (Index::List, self.list.create_graphemes(size.0, size.1)),
+                    (Index::Readline, PaneConstraint::default().priority(2)),
+                    (Index::List, PaneConstraint::default().priority(1)),
                 ],
                 true,
             )
@@ -77,39 +206,128 @@ impl crate::Prompt for QuerySelector {
         Ok(())
     }
 
-    async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        // Store the previous text in the readline before evaluating the event.
-        let prev = self.readline.texteditor.text_without_cursor().to_string();
+    /// Searches the items if the text changed from `prev`, and renders the prompt.
+    /// On `quit`, waits for the search so that the selection reflects the final text.
+    async fn update(&mut self, prev: &str, quit: bool) -> anyhow::Result<()> {
+        // If the text in the readline has changed, search the items in the background,
+        // cancelling the search for the previous text.
+        // Apply the selection moved in the list by the evaluator.
+        let selected = self.list_selected.saturating_sub(1) + self.list.listbox.position();
+        if selected != self.list_selected {
+            self.position = selected;
+        }
 
-        // Evaluate the event using the provided evaluator function.
-        let ret = (self.evaluator)(event, self).await;
-
-        // If the text in the readline has changed, we need to filter the list.
-        if prev != self.readline.texteditor.text_without_cursor().to_string() {
-            let query = self.readline.texteditor.text_without_cursor().to_string();
-            let list = (self.filter)(
-                &query,
-                &self
-                    .init_list
-                    .items()
-                    .iter()
-                    .map(|s| s.to_string())
-                    .collect(),
-            );
-            self.list.listbox = Listbox::from(list);
+        let query = self.readline.texteditor.text_without_cursor().to_string();
+        if prev != query {
+            self.store.search(&query, self.matcher(), self.narrowing);
+            // An empty text lists all the items at once.
+            if self.store.query() == query {
+                self.position = 0;
+            }
         }
 
-        // Update the renderer with the new state of the components.
-        let size = crossterm::terminal::size()?;
-        self.render(size.0, size.1).await?;
+        // Select from the matches for the final text.
+        if quit {
+            let prev = self.store.query().to_string();
+            while let Some(searched) = self.store.wait().await {
+                searched?;
+            }
+            if self.store.query() != prev {
+                self.position = 0;
+            }
+        } else {
+            self.request_preview();
+        }
 
-        ret
+        // Update the renderer with the new state of the components.
+        let size = self.backend.size()?;
+        self.render(size.0, size.1).await
     }
 
-    type Return = String;
+    /// Applies the background work: a completed search, items from the source
+    /// or the preview of the selected item.
+    /// Unless `wait_for_source`, only the items the source has ready are loaded.
+    async fn load(&mut self, wait_for_source: bool) -> Option<anyhow::Result<()>> {
+        let query = self.store.query().to_string();
+        let selected = self.store.index(self.position);
+        let ready = match (&mut self.source, wait_for_source) {
+            (Some(source), false) => source.next().now_or_never(),
+            _ => None,
+        };
+        let work = match ready {
+            Some(item) => Work::Loaded(item),
+            None => {
+                let (store, source, preview) =
+                    (&mut self.store, &mut self.source, &mut self.preview_job);
+                tokio::select! {
+                    Some(searched) = store.wait() => Work::Searched(searched),
+                    item = next_item(source), if wait_for_source && source.is_some() => {
+                        Work::Loaded(item)
+                    }
+                    lines = wait_preview(preview), if preview.is_some() => Work::Previewed(lines),
+                    else => return None,
+                }
+            }
+        };
 
-    fn finalize(&mut self) -> anyhow::Result<Self::Return> {
-        Ok(self.list.listbox.get().to_string())
+        match work {
+            Work::Searched(Err(e)) => return Some(Err(e)),
+            Work::Searched(Ok(())) => {
+                // Keep the selected item if the matches are only updated with new items,
+                // which may be ranked above it.
+                self.position = match selected {
+                    Some(index) if self.store.query() == query => {
+                        self.store.position(index).unwrap_or(0)
+                    }
+                    _ => 0,
+                };
+            }
+            Work::Loaded(Some(item)) => {
+                let mut items = vec![item];
+                if let Some(source) = self.source.as_mut() {
+                    while items.len() < SOURCE_BATCH_SIZE {
+                        match source.next().now_or_never() {
+                            Some(Some(item)) => items.push(item),
+                            Some(None) => {
+                                self.source = None;
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+                self.store.extend(items);
+            }
+            Work::Loaded(None) => self.source = None,
+            Work::Previewed(lines) => {
+                self.preview_job = None;
+                self.preview.text = Text::from_styled_graphemes(lines);
+            }
+        }
+        self.request_preview();
+
+        Some(match self.backend.size() {
+            Ok(size) => self.render(size.0, size.1).await,
+            Err(e) => Err(e),
+        })
+    }
+
+    /// Starts previewing the selected item if it is not the one previewed,
+    /// cancelling the preview in progress.
+    fn request_preview(&mut self) {
+        let Some(previewer) = self.previewer.clone() else {
+            return;
+        };
+        let selected = self.selected().map(str::to_string);
+        if selected == self.previewed {
+            return;
+        }
+        self.preview_job = None;
+        self.preview.text = Text::default();
+        if let Some(item) = &selected {
+            self.preview_job = Some(PreviewJob::spawn(previewer, item.clone()));
+        }
+        self.previewed = selected;
     }
 }
 
@@ -129,10 +347,24 @@ impl QuerySelector {
         T: Display,
         I: IntoIterator<Item = T>,
     {
-        let listbox = Listbox::from(items);
+        Self::with_matcher(items, Matcher::Filter(filter))
+    }
+
+    /// Constructs a new `QuerySelector` instance
+    /// with a list of items and a matcher.
+    pub fn with_matcher<T, I>(items: I, matcher: Matcher) -> Self
+    where
+        T: Display,
+        I: IntoIterator<Item = T>,
+    {
+        let (filter, fuzzy): (Filter, bool) = match matcher {
+            Matcher::Filter(filter) => (filter, false),
+            Matcher::Fuzzy => (|_, items| items.clone(), true),
+        };
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             title: text::State {
                 config: text::config::Config {
                     style: Some(ContentStyle {
@@ -146,6 +378,8 @@ impl QuerySelector {
             readline: text_editor::State {
                 texteditor: Default::default(),
                 history: None,
+                kill_ring: Default::default(),
+                autosuggestion: Default::default(),
                 config: text_editor::config::Config {
                     prefix: String::from("❯❯ "),
                     mask: None,
@@ -158,14 +392,15 @@ impl QuerySelector {
                         ..Default::default()
                     },
                     inactive_char_style: ContentStyle::default(),
+                    autosuggestion_style: ContentStyle::default(),
                     edit_mode: Default::default(),
                     word_break_chars: Default::default(),
                     lines: Default::default(),
                 },
             },
-            init_list: listbox.clone(),
+            store: items.into_iter().collect(),
             list: listbox::State {
-                listbox,
+                listbox: Listbox::default(),
                 config: listbox::config::Config {
                     cursor: String::from("❯ "),
                     active_item_style: Some(ContentStyle {
@@ -176,10 +411,48 @@ impl QuerySelector {
                     lines: Default::default(),
                 },
             },
+            position: 0,
+            list_selected: 0,
             filter,
+            fuzzy,
+            narrowing: false,
+            highlight_style: None,
+            source: None,
+            status: text::State {
+                config: text::config::Config {
+                    style: Some(ContentStyle {
+                        foreground_color: Some(Color::DarkGrey),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            show_status: false,
+            marks: None,
+            previewer: None,
+            preview: Default::default(),
+            preview_position: Default::default(),
+            previewed: None,
+            preview_job: None,
         }
     }
 
+    /// Constructs a new `QuerySelector` instance
+    /// ranking the items with the fuzzy matcher
+    /// and highlighting the matched characters.
+    pub fn fuzzy<T, I>(items: I) -> Self
+    where
+        T: Display,
+        I: IntoIterator<Item = T>,
+    {
+        Self::with_matcher(items, Matcher::Fuzzy).highlight_style(ContentStyle {
+            foreground_color: Some(Color::DarkYellow),
+            attributes: Attributes::from(Attribute::Bold),
+            ..Default::default()
+        })
+    }
+
     /// Sets the title text displayed above the query selection.
     pub fn title<T: AsRef<str>>(mut self, text: T) -> Self {
         self.title.text = Text::from(text);
@@ -246,6 +519,62 @@ impl QuerySelector {
         self
     }
 
+    /// Sets the style for the characters of the items matching the input.
+    pub fn highlight_style(mut self, style: ContentStyle) -> Self {
+        self.highlight_style = Some(style);
+        self
+    }
+
+    /// Sets whether the filter only drops more items as the input grows, like a substring filter.
+    /// If so, an input extending the previous one is matched against the previous matches only.
+    pub fn narrowing(mut self, enabled: bool) -> Self {
+        self.narrowing = enabled;
+        self
+    }
+
+    /// Loads more items from a stream while the prompt is running,
+    /// e.g. the output of a slow command, and shows the status line.
+    /// The items are matched against the input as they arrive.
+    pub fn source<S>(mut self, source: S) -> Self
+    where
+        S: Stream + Send + 'static,
+        S::Item: Display,
+    {
+        self.source = Some(source.map(|item| item.to_string()).boxed());
+        self.show_status = true;
+        self
+    }
+
+    /// Sets whether to show the status line with the number of matched and all items.
+    pub fn show_status(mut self, enabled: bool) -> Self {
+        self.show_status = enabled;
+        self
+    }
+
+    /// Sets the style for the status line.
+    pub fn status_style(mut self, style: ContentStyle) -> Self {
+        self.status.config.style = Some(style);
+        self
+    }
+
+    /// Sets the previewer producing the lines shown below the list for the selected item.
+    pub fn preview<P: Preview + 'static>(mut self, previewer: P) -> Self {
+        self.previewer = Some(Arc::new(previewer));
+        self
+    }
+
+    /// Sets where the preview pane is placed relative to the list.
+    pub fn preview_position(mut self, position: PreviewPosition) -> Self {
+        self.preview_position = position;
+        self
+    }
+
+    /// Sets the number of lines available for the preview pane.
+    pub fn preview_lines(mut self, lines: usize) -> Self {
+        self.preview.config.lines = Some(lines);
+        self
+    }
+
     /// Sets the number of lines available for the list box component.
     pub fn listbox_lines(mut self, lines: usize) -> Self {
         self.list.config.lines = Some(lines);
@@ -253,28 +582,423 @@ impl QuerySelector {
     }
 
     /// Sets the evaluator function for the text prompt.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
         self
     }
 
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
+        self
+    }
+
+    /// Selects the previous match. Returns whether the selection moved.
+    pub fn backward(&mut self) -> bool {
+        let moved = self.position > 0;
+        self.position = self.position.saturating_sub(1);
+        moved
+    }
+
+    /// Selects the next match. Returns whether the selection moved.
+    pub fn forward(&mut self) -> bool {
+        let moved = self.position + 1 < self.store.len();
+        if moved {
+            self.position += 1;
+        }
+        moved
+    }
+
+    /// Returns the selected item, if any.
+    pub fn selected(&self) -> Option<&str> {
+        self.store.get(self.position)
+    }
+
+    /// Returns the matcher for `fuzzy` and `filter`.
+    fn matcher(&self) -> Matcher {
+        if self.fuzzy {
+            Matcher::Fuzzy
+        } else {
+            Matcher::Filter(self.filter)
+        }
+    }
+
+    /// Creates the graphemes of the status line, e.g. `12/3456 items, loading…`.
+    fn status_graphemes(&mut self, width: u16, height: u16) -> StyledGraphemes {
+        if !self.show_status {
+            return StyledGraphemes::default();
+        }
+        self.status.text = Text::from(format!(
+            "{}/{} items{}",
+            self.store.len(),
+            self.store.total(),
+            if self.source.is_some() {
+                ", loading…"
+            } else {
+                ""
+            },
+        ));
+        self.status.create_graphemes(width, height)
+    }
+
+    /// Creates the graphemes of all the panes.
+    /// With a preview, the list gets the rows left by the other panes.
+    /// A preview on the right is composed with the list into the list pane.
+    fn panes(&mut self, width: u16, height: u16) -> [(Index, StyledGraphemes); 5] {
+        let title = self.title.create_graphemes(width, height);
+        let readline = self.readline.create_graphemes(width, height);
+        let status = self.status_graphemes(width, height);
+        let rows = [&title, &readline, &status]
+            .iter()
+            .map(|pane| pane.wrapped_lines(width as usize).len() as u16)
+            .sum::<u16>();
+        let (list, preview) = match (&self.previewer, self.preview_position) {
+            (None, _) => (self.list_graphemes(width, height), Default::default()),
+            (Some(_), PreviewPosition::Bottom) => {
+                let preview_height = match self.preview.config.lines {
+                    Some(lines) => lines.min(height as usize),
+                    None => height as usize / 2,
+                } as u16;
+                let list_height = 1.max(height.saturating_sub(rows + preview_height));
+                (
+                    self.list_graphemes(width, list_height),
+                    self.preview.create_graphemes(width, preview_height),
+                )
+            }
+            (Some(_), PreviewPosition::Right) => {
+                let height = 1.max(height.saturating_sub(rows));
+                // The list, a gap and the preview.
+                let sizes = [Size::Flex(1), Size::Fixed(1), Size::Percentage(50)];
+                let list = self.list_graphemes(layout::split(&sizes, width)[0], height);
+                let gap = StyledGraphemes::default();
+                let pane = Layout::columns()
+                    .push(sizes[0], &list)
+                    .push(sizes[1], &gap)
+                    .push(sizes[2], &self.preview)
+                    .create_graphemes(width, height);
+                (pane, Default::default())
+            }
+        };
+        [
+            (Index::Title, title),
+            (Index::Readline, readline),
+            (Index::Status, status),
+            (Index::List, list),
+            (Index::Preview, preview),
+        ]
+    }
+
+    /// Creates the graphemes of the list box for the matches from the selected one,
+    /// only styling the items that fit in the pane.
+    /// The list also holds the item above the selected one,
+    /// so that the evaluator can move the selection either way.
+    fn list_graphemes(&mut self, width: u16, height: u16) -> StyledGraphemes {
+        let lines = match self.list.config.lines {
+            Some(lines) => lines.min(height as usize),
+            None => height as usize,
+        };
+        self.position = self.position.min(self.store.len().saturating_sub(1));
+        self.list_selected = self.position;
+        let start = self.position.saturating_sub(1);
+        let query = self.store.query();
+        let items = (start..self.store.len())
+            .take(lines + self.position - start)
+            .filter_map(|i| Some((self.store.index(i)?, self.store.get(i)?)))
+            .map(|(index, item)| {
+                let item = match self.highlight_style {
+                    Some(style) => fuzzy::highlight(query, item, style),
+                    None => StyledGraphemes::from(item),
+                };
+                match &self.marks {
+                    Some(marks) => StyledGraphemes::from_iter([marks.mark(index), item]),
+                    None => item,
+                }
+            })
+            .collect();
+        self.list.listbox = Listbox::from_styled_graphemes(items);
+        if start < self.position {
+            self.list.listbox.forward();
+        }
+        self.list.create_graphemes(width, height)
+    }
+
     /// Render the prompt with the specified width and height.
     async fn render(&mut self, width: u16, height: u16) -> anyhow::Result<()> {
+        let panes = self.panes(width, height);
         match self.renderer.as_ref() {
-            Some(renderer) => {
-                renderer
-                    .update([
-                        (Index::Title, self.title.create_graphemes(width, height)),
-                        (
-                            Index::Readline,
-                            self.readline.create_graphemes(width, height),
-                        ),
-                        (Index::List, self.list.create_graphemes(width, height)),
-                    ])
-                    .render()
-                    .await
-            }
+            Some(renderer) => renderer.update(panes).render().await,
             None => Err(anyhow::anyhow!("Renderer not initialized")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{
+            backend::Memory,
+            crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+        },
+        test_util::{key, key_with, type_str},
+        Prompt,
+    };
+
+    mod load {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_keeps_selected_item() {
+            let mut selector = QuerySelector::fuzzy(["xaxb", "zz"]).backend(Memory::new(20, 10));
+            selector
+                .run_with_events([key(KeyCode::Char('a')), key(KeyCode::Char('b'))])
+                .await
+                .unwrap();
+            assert_eq!(Some("xaxb"), selector.selected());
+
+            // A better match arrives while the item is selected.
+            selector.source = Some(futures::stream::iter([String::from("ab")]).boxed());
+            while let Some(ret) = selector.load(true).await {
+                ret.unwrap();
+            }
+            assert_eq!(Some("ab"), selector.store.get(0));
+            assert_eq!(Some("xaxb"), selector.selected());
+        }
+    }
+
+    mod run_with_events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let memory = Memory::new(20, 10);
+            let mut selector = QuerySelector::new(["apple", "banana", "cherry"], |text, items| {
+                items
+                    .iter()
+                    .filter(|item| item.contains(text))
+                    .cloned()
+                    .collect()
+            })
+            .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([
+                    key(KeyCode::Char('a')),
+                    key(KeyCode::Char('n')),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!("banana", ret);
+            assert!(memory.lines().iter().any(|line| line.ends_with("banana")));
+            assert!(!memory.lines().iter().any(|line| line.ends_with("apple")));
+        }
+
+        #[tokio::test]
+        async fn test_with_list_evaluator() {
+            let mut selector = QuerySelector::fuzzy(["apple", "banana", "cherry"])
+                .evaluator(|event, ctx| {
+                    Box::pin(async move {
+                        match event {
+                            Event::Key(KeyEvent {
+                                code: KeyCode::Char('j'),
+                                ..
+                            }) => {
+                                ctx.list.listbox.forward();
+                            }
+                            Event::Key(KeyEvent {
+                                code: KeyCode::Char('k'),
+                                ..
+                            }) => {
+                                ctx.list.listbox.backward();
+                            }
+                            _ => return evaluate::default(event, ctx).await,
+                        }
+                        Ok(Signal::Continue)
+                    })
+                })
+                .backend(Memory::new(20, 10));
+            // The filter set by an evaluator is applied from the next input.
+            selector.filter = |text, items| {
+                items
+                    .iter()
+                    .filter(|item| !item.contains(text))
+                    .cloned()
+                    .collect()
+            };
+            selector.fuzzy = false;
+
+            let events = type_str("jjk\n");
+            assert_eq!("banana", selector.run_with_events(events).await.unwrap());
+
+            let events = [key(KeyCode::Char('n')), key(KeyCode::Enter)];
+            assert_eq!("apple", selector.run_with_events(events).await.unwrap());
+            assert_eq!(2, selector.store.len());
+        }
+
+        #[tokio::test]
+        async fn test_with_scrolling() {
+            let memory = Memory::new(20, 10);
+            let mut selector = QuerySelector::fuzzy(0..100)
+                .listbox_lines(3)
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events(
+                    std::iter::repeat_n(key(KeyCode::Down), 5)
+                        .chain([key(KeyCode::Up), key(KeyCode::Enter)]),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!("4", ret);
+            assert!(memory.lines().iter().any(|line| line.ends_with("6")));
+            assert!(!memory.lines().iter().any(|line| line.ends_with("7")));
+        }
+
+        #[tokio::test]
+        async fn test_with_source() {
+            let memory = Memory::new(30, 10);
+            let (tx, rx) = futures::channel::mpsc::unbounded();
+            tx.unbounded_send("apple").unwrap();
+            tx.unbounded_send("banana").unwrap();
+            let mut selector = QuerySelector::fuzzy(["cherry"])
+                .source(rx)
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([
+                    key(KeyCode::Char('a')),
+                    key(KeyCode::Char('n')),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!("banana", ret);
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line.ends_with("1/3 items, loading…")));
+        }
+
+        #[tokio::test]
+        async fn test_with_finished_source() {
+            let memory = Memory::new(30, 10);
+            let mut selector = QuerySelector::fuzzy(Vec::<String>::new())
+                .source(futures::stream::iter(0..10))
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([key(KeyCode::Char('7')), key(KeyCode::Enter)])
+                .await
+                .unwrap();
+
+            assert_eq!("7", ret);
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line.ends_with("1/10 items")));
+        }
+
+        #[tokio::test]
+        async fn test_with_preview() {
+            let memory = Memory::new(30, 10);
+            let mut selector = QuerySelector::fuzzy(["apple", "banana"])
+                .preview(|item: &str| vec![StyledGraphemes::from(format!("preview of {item}"))])
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([key(KeyCode::Down), key(KeyCode::Enter)])
+                .await
+                .unwrap();
+
+            assert_eq!("banana", ret);
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line.ends_with("preview of banana")));
+            assert!(!memory
+                .lines()
+                .iter()
+                .any(|line| line.ends_with("preview of apple")));
+        }
+
+        #[tokio::test]
+        async fn test_with_preview_scrolling() {
+            let memory = Memory::new(30, 10);
+            let mut selector = QuerySelector::fuzzy(["apple"])
+                .preview(|item: &str| {
+                    (0..5)
+                        .map(|i| StyledGraphemes::from(format!("{item} line {i}")))
+                        .collect()
+                })
+                .preview_lines(2)
+                .backend(memory.clone());
+
+            selector
+                .run_with_events([
+                    key_with(KeyCode::Down, KeyModifiers::SHIFT),
+                    key_with(KeyCode::Down, KeyModifiers::SHIFT),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            let lines = memory.lines();
+            assert!(lines.iter().any(|line| line.ends_with("apple line 3")));
+            assert!(!lines.iter().any(|line| line.ends_with("apple line 1")));
+            assert!(!lines.iter().any(|line| line.ends_with("apple line 4")));
+        }
+
+        #[tokio::test]
+        async fn test_with_preview_on_the_right() {
+            let memory = Memory::new(20, 10);
+            let mut selector = QuerySelector::fuzzy(["apple", "banana"])
+                .preview(|item: &str| vec![StyledGraphemes::from(item.to_uppercase())])
+                .preview_position(PreviewPosition::Right)
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([key(KeyCode::Down), key(KeyCode::Enter)])
+                .await
+                .unwrap();
+
+            assert_eq!("banana", ret);
+            let lines = memory.lines();
+            assert!(lines.iter().any(|line| line == "❯ banana  BANANA"));
+        }
+
+        #[tokio::test]
+        async fn test_with_fuzzy() {
+            let memory = Memory::new(20, 10);
+            let mut selector = QuerySelector::fuzzy(["Cargo.toml", "src/main.rs", "src/lib.rs"])
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([
+                    key(KeyCode::Char('s')),
+                    key(KeyCode::Char('m')),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!("src/main.rs", ret);
+            assert!(!memory.lines().iter().any(|line| line.ends_with("lib.rs")));
+            let highlighted = selector.list.listbox.get();
+            let style = selector.highlight_style.unwrap();
+            assert_eq!(
+                StyledGraphemes::from("src/main.rs")
+                    .apply_style_at(0, style)
+                    .apply_style_at(4, style),
+                highlighted
+            );
+        }
+    }
+}
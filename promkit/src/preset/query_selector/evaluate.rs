@@ -68,7 +68,38 @@ pub async fn default(event: &Event, ctx: &mut QuerySelector) -> anyhow::Result<S
             modifiers: KeyModifiers::CONTROL,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => ctx.readline.texteditor.erase_all(),
+        }) => ctx.readline.kill_all(),
+
+        // Undo / redo.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        // Terminals send Ctrl + _ as 0x1F, which is read as Ctrl + 7.
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('7'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.texteditor.undo();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.texteditor.redo();
+        }
 
         // Move cursor.
         Event::Key(KeyEvent {
@@ -77,7 +108,7 @@ pub async fn default(event: &Event, ctx: &mut QuerySelector) -> anyhow::Result<S
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }) => {
-            ctx.list.listbox.backward();
+            ctx.backward();
         }
 
         Event::Key(KeyEvent {
@@ -86,7 +117,25 @@ pub async fn default(event: &Event, ctx: &mut QuerySelector) -> anyhow::Result<S
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }) => {
-            ctx.list.listbox.forward();
+            ctx.forward();
+        }
+
+        // Scroll the preview.
+        Event::Key(KeyEvent {
+            code: KeyCode::Up,
+            modifiers: KeyModifiers::SHIFT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.preview.text.backward();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Down,
+            modifiers: KeyModifiers::SHIFT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.preview.text.forward();
         }
 
         // Input char.
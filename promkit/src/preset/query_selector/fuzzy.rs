@@ -0,0 +1,457 @@
+//! An fzf-style fuzzy matcher for [`QuerySelector`](super::QuerySelector).
+//!
+//! The query is split on whitespace into terms, and an item is kept
+//! only if it matches all of them. Each term is one of:
+//!
+//! | Term      | Matches items                          |
+//! | --------- | -------------------------------------- |
+//! | `sbtrkt`  | containing `s`, `b`, `t`, ... in order |
+//! | `'wild`   | containing `wild`                      |
+//! | `^music`  | starting with `music`                  |
+//! | `.mp3$`   | ending with `.mp3`                     |
+//! | `^a.txt$` | equal to `a.txt`                       |
+//! | `!fire`   | not containing `fire`                  |
+//!
+//! A term is matched case-insensitively unless it contains an uppercase letter.
+//! The kept items are ranked by how tightly and where they match,
+//! preferring consecutive characters and matches at the start of words.
+
+use crate::core::{crossterm::style::ContentStyle, grapheme::StyledGraphemes};
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+/// Bonus for a match at the start of a word, e.g. after a space or `/`.
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+/// Bonus for matching a non-word character, e.g. a `/` in a path.
+const BONUS_NON_WORD: i64 = SCORE_MATCH / 2;
+/// Bonus for a match at a camelCase or letter-to-number transition.
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+/// Minimum bonus for a match following another match.
+const BONUS_CONSECUTIVE: i64 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+/// The bonus of the first character of a term counts this many times.
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+}
+
+impl CharClass {
+    fn of(ch: char) -> Self {
+        if ch.is_lowercase() {
+            CharClass::Lower
+        } else if ch.is_uppercase() {
+            CharClass::Upper
+        } else if ch.is_numeric() {
+            CharClass::Number
+        } else if ch.is_alphabetic() {
+            // Letters without case, e.g. CJK.
+            CharClass::Lower
+        } else {
+            CharClass::NonWord
+        }
+    }
+
+    /// Returns the bonus for matching a character of class `self`
+    /// right after a character of class `prev`.
+    fn bonus(self, prev: Self) -> i64 {
+        match (prev, self) {
+            (CharClass::NonWord, class) if class != CharClass::NonWord => BONUS_BOUNDARY,
+            (CharClass::Lower, CharClass::Upper) => BONUS_CAMEL,
+            (prev, CharClass::Number) if prev != CharClass::Number => BONUS_CAMEL,
+            (_, CharClass::NonWord) => BONUS_NON_WORD,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Fuzzy,
+    Exact,
+    Prefix,
+    Suffix,
+    Equal,
+}
+
+struct Term {
+    kind: Kind,
+    negated: bool,
+    case_sensitive: bool,
+    chars: Vec<char>,
+}
+
+impl Term {
+    fn parse(s: &str) -> Option<Self> {
+        let (negated, s) = match s.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (anchored_start, s) = match s.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (anchored_end, s) = match s.strip_suffix('$') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, s),
+        };
+        let (quoted, s) = match s.strip_prefix('\'') {
+            Some(rest) if !anchored_start => (true, rest),
+            _ => (false, s),
+        };
+        if s.is_empty() {
+            return None;
+        }
+
+        let kind = match (anchored_start, anchored_end) {
+            (true, true) => Kind::Equal,
+            (true, false) => Kind::Prefix,
+            (false, true) => Kind::Suffix,
+            // Negated terms never match fuzzily, as in fzf.
+            (false, false) if quoted || negated => Kind::Exact,
+            (false, false) => Kind::Fuzzy,
+        };
+        let case_sensitive = s.chars().any(char::is_uppercase);
+        Some(Self {
+            kind,
+            negated,
+            case_sensitive,
+            chars: s.chars().map(|ch| fold(ch, case_sensitive)).collect(),
+        })
+    }
+
+    /// Returns the score and the matched char indices, if `text` matches the term
+    /// (ignoring negation). `folded` is `text` with the term's case folding applied.
+    fn find(&self, text: &[char], folded: &[char]) -> Option<(i64, Vec<usize>)> {
+        let len = self.chars.len();
+        let starts_at = |start: usize| folded[start..].starts_with(&self.chars);
+        match self.kind {
+            Kind::Fuzzy => fuzzy_range(folded, &self.chars)
+                .map(|(start, end)| score(text, folded, &self.chars, start, end)),
+            Kind::Exact => (0..=folded.len().saturating_sub(len))
+                .filter(|&start| folded.len() >= len && starts_at(start))
+                .map(|start| score(text, folded, &self.chars, start, start + len))
+                .max_by_key(|(score, _)| *score),
+            Kind::Prefix => (folded.len() >= len && starts_at(0))
+                .then(|| score(text, folded, &self.chars, 0, len)),
+            Kind::Suffix => (folded.len() >= len && starts_at(folded.len() - len))
+                .then(|| score(text, folded, &self.chars, folded.len() - len, folded.len())),
+            Kind::Equal => {
+                (folded == self.chars.as_slice()).then(|| score(text, folded, &self.chars, 0, len))
+            }
+        }
+    }
+}
+
+fn fold(ch: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        ch
+    } else {
+        ch.to_lowercase().next().unwrap_or(ch)
+    }
+}
+
+/// Finds the shortest range ending at the first complete match of `pattern`
+/// as a subsequence: scans forward for the end, then backward for the start.
+fn fuzzy_range(text: &[char], pattern: &[char]) -> Option<(usize, usize)> {
+    let mut pidx = 0;
+    let mut end = None;
+    for (i, ch) in text.iter().enumerate() {
+        if *ch == pattern[pidx] {
+            pidx += 1;
+            if pidx == pattern.len() {
+                end = Some(i + 1);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut pidx = pattern.len();
+    let mut start = end;
+    while pidx > 0 {
+        start -= 1;
+        if text[start] == pattern[pidx - 1] {
+            pidx -= 1;
+        }
+    }
+    Some((start, end))
+}
+
+/// Scores the match of `pattern` within `folded[start..end]`,
+/// returning the score and the matched char indices.
+fn score(
+    text: &[char],
+    folded: &[char],
+    pattern: &[char],
+    start: usize,
+    end: usize,
+) -> (i64, Vec<usize>) {
+    let mut score = 0;
+    let mut indices = Vec::with_capacity(pattern.len());
+    let mut pidx = 0;
+    let mut in_gap = false;
+    let mut consecutive = 0;
+    let mut first_bonus = 0;
+    let mut prev_class = match start {
+        0 => CharClass::NonWord,
+        _ => CharClass::of(text[start - 1]),
+    };
+
+    for idx in start..end {
+        let class = CharClass::of(text[idx]);
+        if pidx < pattern.len() && folded[idx] == pattern[pidx] {
+            indices.push(idx);
+            score += SCORE_MATCH;
+            let mut bonus = class.bonus(prev_class);
+            if consecutive == 0 {
+                first_bonus = bonus;
+            } else {
+                // Keep the bonus of the first character for the whole chunk.
+                if bonus >= BONUS_BOUNDARY && bonus > first_bonus {
+                    first_bonus = bonus;
+                }
+                bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            score += if pidx == 0 {
+                bonus * BONUS_FIRST_CHAR_MULTIPLIER
+            } else {
+                bonus
+            };
+            in_gap = false;
+            consecutive += 1;
+            pidx += 1;
+        } else {
+            score += if in_gap {
+                SCORE_GAP_EXTENSION
+            } else {
+                SCORE_GAP_START
+            };
+            in_gap = true;
+            consecutive = 0;
+            first_bonus = 0;
+        }
+        prev_class = class;
+    }
+    (score, indices)
+}
+
+/// A parsed query.
+pub(super) struct Query(Vec<Term>);
+
+impl Query {
+    pub(super) fn parse(query: &str) -> Self {
+        Self(query.split_whitespace().filter_map(Term::parse).collect())
+    }
+
+    /// Returns whether the query ranks the items, i.e. has a term that is not negated.
+    pub(super) fn ranks(&self) -> bool {
+        self.0.iter().any(|term| !term.negated)
+    }
+
+    pub(super) fn find(&self, item: &str) -> Option<(i64, Vec<usize>)> {
+        let text: Vec<char> = item.chars().collect();
+        let lower: Vec<char> = text.iter().map(|ch| fold(*ch, false)).collect();
+
+        let mut total = 0;
+        let mut indices = Vec::new();
+        for term in &self.0 {
+            let folded = if term.case_sensitive { &text } else { &lower };
+            match (term.find(&text, folded), term.negated) {
+                (Some((score, found)), false) => {
+                    total += score;
+                    indices.extend(found);
+                }
+                (None, true) => (),
+                _ => return None,
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        Some((total, indices))
+    }
+}
+
+/// Returns whether every item matching `query` also matches `prev`,
+/// so that `query` only needs to be matched against the matches of `prev`.
+pub(super) fn narrows(prev: &str, query: &str) -> bool {
+    let Some(rest) = query.strip_prefix(prev) else {
+        return false;
+    };
+    if rest.is_empty() || prev.ends_with(char::is_whitespace) {
+        return true;
+    }
+    // Extending a negated or suffix term may match more items, e.g. `!a` to `!ab`.
+    prev.split_whitespace()
+        .last()
+        .is_none_or(|term| !term.starts_with('!') && !term.ends_with('$'))
+}
+
+/// Returns the score and the indices of the matched chars in `item`,
+/// or `None` if `item` does not match `query`.
+pub fn find(query: &str, item: &str) -> Option<(i64, Vec<usize>)> {
+    Query::parse(query).find(item)
+}
+
+/// Keeps the items matching `query`, best matches first.
+/// Items with equal scores are ordered by length, then by their original order.
+/// It can be passed as a [`Filter`](super::Filter), while
+/// [`QuerySelector::fuzzy`](super::QuerySelector::fuzzy) ranks the items the same way in parallel.
+#[allow(clippy::ptr_arg)] // Matches the signature of `Filter`.
+pub fn filter(query: &str, items: &Vec<String>) -> Vec<String> {
+    let query = Query::parse(query);
+    let mut matched: Vec<(i64, &String)> = items
+        .iter()
+        .filter_map(|item| query.find(item).map(|(score, _)| (score, item)))
+        .collect();
+    if query.ranks() {
+        matched.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| a.1.chars().count().cmp(&b.1.chars().count()))
+        });
+    }
+    matched.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
+/// Returns `item` as graphemes with `style` applied to the chars matching `query`.
+pub fn highlight(query: &str, item: &str, style: ContentStyle) -> StyledGraphemes {
+    let mut ret = StyledGraphemes::from(item);
+    let Some((_, indices)) = find(query, item) else {
+        return ret;
+    };
+
+    // Map char indices to grapheme indices, as a grapheme may consist of several chars.
+    let graphemes: Vec<usize> = ret
+        .iter()
+        .enumerate()
+        .flat_map(|(i, grapheme)| std::iter::repeat_n(i, grapheme.as_str().chars().count()))
+        .collect();
+    let mut prev = None;
+    for idx in indices {
+        let grapheme = graphemes[idx];
+        if prev != Some(grapheme) {
+            ret = ret.apply_style_at(grapheme, style);
+            prev = Some(grapheme);
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    mod find {
+        use super::*;
+
+        #[test]
+        fn test_with_fuzzy_term() {
+            assert_eq!(Some(vec![0, 4, 8]), find("abc", "a___b___c").map(|m| m.1));
+            assert_eq!(None, find("acb", "a___b___c"));
+            // The shortest range is preferred over the first occurrence.
+            assert_eq!(Some(vec![4, 5]), find("ab", "a___ab").map(|m| m.1));
+        }
+
+        #[test]
+        fn test_with_smart_case() {
+            assert!(find("foo", "FooBar").is_some());
+            assert!(find("Foo", "FooBar").is_some());
+            assert!(find("Foo", "foobar").is_none());
+        }
+
+        #[test]
+        fn test_with_anchors() {
+            assert!(find("^src", "src/main.rs").is_some());
+            assert!(find("^main", "src/main.rs").is_none());
+            assert!(find(".rs$", "src/main.rs").is_some());
+            assert!(find("src$", "src/main.rs").is_none());
+            assert!(find("^main.rs$", "main.rs").is_some());
+            assert!(find("^main.rs$", "src/main.rs").is_none());
+            assert_eq!(
+                Some(vec![4, 5, 6, 7]),
+                find("'main", "src/main.rs").map(|m| m.1)
+            );
+            assert!(find("'smn", "src/main.rs").is_none());
+        }
+
+        #[test]
+        fn test_with_multiple_terms() {
+            assert_eq!(
+                Some(vec![0, 1, 2, 9, 10]),
+                find("src rs", "src/main.rs").map(|m| m.1)
+            );
+            assert!(find("src !test", "src/main.rs").is_some());
+            assert!(find("src !main", "src/main.rs").is_none());
+            assert!(find("!", "src/main.rs").is_some());
+        }
+    }
+
+    mod filter {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let items = items(&["fuzzy_finder.rs", "fzf", "foo_zoo_fab", "bar"]);
+            assert_eq!(
+                vec!["fzf", "foo_zoo_fab", "fuzzy_finder.rs"],
+                filter("fzf", &items)
+            );
+        }
+
+        #[test]
+        fn test_with_boundary_bonus() {
+            let items = items(&["xxmainxx", "src/main.rs"]);
+            assert_eq!(vec!["src/main.rs", "xxmainxx"], filter("main", &items));
+        }
+
+        #[test]
+        fn test_without_ranking() {
+            let items = items(&["banana", "apple", "cherry"]);
+            assert_eq!(items, filter("", &items));
+            assert_eq!(vec!["banana", "cherry"], filter("!apple", &items));
+        }
+    }
+
+    mod narrows {
+        use super::*;
+
+        #[test]
+        fn test() {
+            assert!(narrows("", "ab"));
+            assert!(narrows("a", "ab"));
+            assert!(narrows("a", "a !b"));
+            assert!(narrows("!a ", "!a b"));
+            assert!(narrows("^a", "^a$"));
+            assert!(!narrows("ab", "a"));
+            assert!(!narrows("!a", "!ab"));
+            assert!(!narrows("a$", "a$b"));
+        }
+    }
+
+    mod highlight {
+        use super::*;
+
+        use crate::core::crossterm::style::Color;
+
+        #[test]
+        fn test() {
+            let style = ContentStyle {
+                foreground_color: Some(Color::Yellow),
+                ..Default::default()
+            };
+            let expected = StyledGraphemes::from("e\u{301}x")
+                .apply_style_at(0, style)
+                .apply_style_at(1, style);
+            assert_eq!(expected, highlight("e\u{301}x", "e\u{301}x", style));
+            assert_eq!(StyledGraphemes::from("abc"), highlight("z", "abc", style));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Filters a set of options by a query and picks several of them.
+
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::{
+    core::{backend::Backend, crossterm::event::Event, grapheme::StyledGraphemes},
+    preset::{self, EvaluateFuture, Evaluator},
+    Signal,
+};
+
+use super::{Filter, QuerySelector};
+
+pub mod evaluate;
+
+/// The picked items and the marks rendered before the items.
+pub(super) struct Marks {
+    /// Indices of the picked items in [`ItemStore::items`](super::ItemStore::items).
+    picked: BTreeSet<usize>,
+    active: char,
+    inactive: char,
+}
+
+impl Default for Marks {
+    fn default() -> Self {
+        Self {
+            picked: BTreeSet::new(),
+            active: '☒',
+            inactive: '☐',
+        }
+    }
+}
+
+impl Marks {
+    /// Returns the mark for the item at the given index.
+    pub(super) fn mark(&self, index: usize) -> StyledGraphemes {
+        if self.picked.contains(&index) {
+            StyledGraphemes::from(format!("{} ", self.active))
+        } else {
+            StyledGraphemes::from(format!("{} ", self.inactive))
+        }
+    }
+}
+
+/// A query selector where several items can be picked,
+/// like a checkbox narrowed down by the input.
+///
+/// The picked items stay picked while the input changes.
+/// Configure the query selector, e.g. its title, before converting it:
+/// `MultiQuerySelector::from(QuerySelector::fuzzy(items).title("Pick files"))`.
+pub struct MultiQuerySelector {
+    /// Function to evaluate the input events and update the state of the prompt.
+    pub evaluator: Evaluator<Self>,
+    /// The query selector filtering and listing the items.
+    pub selector: QuerySelector,
+}
+
+impl From<QuerySelector> for MultiQuerySelector {
+    fn from(mut selector: QuerySelector) -> Self {
+        selector.marks = Some(Marks::default());
+        Self {
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            selector,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::Prompt for MultiQuerySelector {
+    async fn initialize(&mut self) -> anyhow::Result<()> {
+        self.selector.init_renderer().await
+    }
+
+    async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
+        let prev = self
+            .selector
+            .readline
+            .texteditor
+            .text_without_cursor()
+            .to_string();
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        self.selector
+            .update(&prev, matches!(ret, Ok(Signal::Quit)))
+            .await?;
+        ret
+    }
+
+    async fn background(&mut self) -> Option<anyhow::Result<Signal>> {
+        self.selector
+            .load(true)
+            .await
+            .map(|ret| ret.map(|()| Signal::Continue))
+    }
+
+    async fn settle(&mut self) -> Option<anyhow::Result<Signal>> {
+        self.selector
+            .load(false)
+            .await
+            .map(|ret| ret.map(|()| Signal::Continue))
+    }
+
+    type Return = Vec<String>;
+
+    /// Returns the picked items in their original order,
+    /// or the selected item if none is picked.
+    fn finalize(&mut self) -> anyhow::Result<Self::Return> {
+        let picked = self.picked();
+        if picked.is_empty() {
+            return Ok(self
+                .selector
+                .selected()
+                .map(|item| vec![item.to_string()])
+                .unwrap_or_default());
+        }
+        Ok(picked.into_iter().map(str::to_string).collect())
+    }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.selector.backend.clone()
+    }
+}
+
+impl MultiQuerySelector {
+    /// Constructs a new `MultiQuerySelector` instance
+    /// with a list of items and a filter function, see [`QuerySelector::new`].
+    pub fn new<T, I>(items: I, filter: Filter) -> Self
+    where
+        T: Display,
+        I: IntoIterator<Item = T>,
+    {
+        QuerySelector::new(items, filter).into()
+    }
+
+    /// Constructs a new `MultiQuerySelector` instance
+    /// ranking the items with the fuzzy matcher, see [`QuerySelector::fuzzy`].
+    pub fn fuzzy<T, I>(items: I) -> Self
+    where
+        T: Display,
+        I: IntoIterator<Item = T>,
+    {
+        QuerySelector::fuzzy(items).into()
+    }
+
+    /// Sets the mark for the picked items.
+    pub fn active_mark(mut self, mark: char) -> Self {
+        self.marks().active = mark;
+        self
+    }
+
+    /// Sets the mark for the items not picked.
+    pub fn inactive_mark(mut self, mark: char) -> Self {
+        self.marks().inactive = mark;
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.selector.backend = Box::new(backend);
+        self
+    }
+
+    /// Sets the evaluator function for the prompt.
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Picks the selected item, or unpicks it if picked.
+    /// Returns whether there was an item to toggle.
+    pub fn toggle(&mut self) -> bool {
+        let Some(index) = self.selector.store.index(self.selector.position) else {
+            return false;
+        };
+        let picked = &mut self.marks().picked;
+        if !picked.remove(&index) {
+            picked.insert(index);
+        }
+        true
+    }
+
+    /// Returns the picked items in their original order.
+    pub fn picked(&self) -> Vec<&str> {
+        let items = self.selector.store.items();
+        self.selector
+            .marks
+            .iter()
+            .flat_map(|marks| marks.picked.iter())
+            .map(|&index| items[index].as_str())
+            .collect()
+    }
+
+    fn marks(&mut self) -> &mut Marks {
+        self.selector.marks.get_or_insert_with(Marks::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{backend::Memory, crossterm::event::KeyCode},
+        test_util::key,
+        Prompt,
+    };
+
+    mod run_with_events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let memory = Memory::new(20, 10);
+            let mut selector = MultiQuerySelector::fuzzy(["apple", "banana", "cherry", "grape"])
+                .backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([
+                    key(KeyCode::Char('a')),
+                    key(KeyCode::Tab),
+                    key(KeyCode::Backspace),
+                    key(KeyCode::Char('r')),
+                    key(KeyCode::Down),
+                    key(KeyCode::Tab),
+                    key(KeyCode::Enter),
+                ])
+                .await
+                .unwrap();
+
+            assert_eq!(vec!["apple", "cherry"], ret);
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line.ends_with("❯ ☒ cherry")));
+        }
+
+        #[tokio::test]
+        async fn test_without_picks() {
+            let memory = Memory::new(20, 10);
+            let mut selector =
+                MultiQuerySelector::fuzzy(["apple", "banana"]).backend(memory.clone());
+
+            let ret = selector
+                .run_with_events([key(KeyCode::Down), key(KeyCode::Enter)])
+                .await
+                .unwrap();
+
+            assert_eq!(vec!["banana"], ret);
+        }
+    }
+}
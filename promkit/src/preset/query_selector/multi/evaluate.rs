@@ -0,0 +1,38 @@
+use crate::{
+    core::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+    preset::query_selector::{self, MultiQuerySelector},
+    Signal,
+};
+
+/// Default key bindings for the multi-select query selector,
+/// in addition to those of [`query_selector::evaluate::default`].
+///
+/// | Key                    | Action
+/// | :--------------------- | :-------------------------------------------
+/// | <kbd>Tab</kbd>         | Toggle the current item and move the selection down
+/// | <kbd>Shift + Tab</kbd> | Toggle the current item and move the selection up
+pub async fn default(event: &Event, ctx: &mut MultiQuerySelector) -> anyhow::Result<Signal> {
+    match event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Tab,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.toggle();
+            ctx.selector.forward();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::BackTab,
+            modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.toggle();
+            ctx.selector.backward();
+        }
+
+        _ => return query_selector::evaluate::default(event, &mut ctx.selector).await,
+    }
+    Ok(Signal::Continue)
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+
+use crate::core::grapheme::StyledGraphemes;
+
+/// Produces the lines shown in the preview pane for the selected item,
+/// e.g. the contents of a file or the details of a commit.
+///
+/// Previews are produced in the background, and the preview in progress is
+/// cancelled when another item is selected, so implementations may run commands.
+/// Synchronous closures of `Fn(&str) -> Vec<StyledGraphemes>` implement this trait as well.
+#[async_trait::async_trait]
+pub trait Preview: Send + Sync {
+    /// Returns the lines to show for the item.
+    /// An error is shown in place of the preview.
+    async fn preview(&self, item: &str) -> anyhow::Result<Vec<StyledGraphemes>>;
+}
+
+#[async_trait::async_trait]
+impl<F> Preview for F
+where
+    F: Fn(&str) -> Vec<StyledGraphemes> + Send + Sync,
+{
+    async fn preview(&self, item: &str) -> anyhow::Result<Vec<StyledGraphemes>> {
+        Ok(self(item))
+    }
+}
+
+/// Where the preview pane is placed relative to the list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PreviewPosition {
+    /// Below the list, taking the lines set for the preview.
+    #[default]
+    Bottom,
+    /// Next to the list, taking half of the width.
+    Right,
+}
+
+/// A preview running on the runtime, aborted when dropped.
+pub(super) struct PreviewJob {
+    handle: JoinHandle<anyhow::Result<Vec<StyledGraphemes>>>,
+}
+
+impl Drop for PreviewJob {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl PreviewJob {
+    pub(super) fn spawn(previewer: Arc<dyn Preview>, item: String) -> Self {
+        Self {
+            handle: tokio::spawn(async move { previewer.preview(&item).await }),
+        }
+    }
+
+    /// Waits for the preview, returning the error message as the lines on failure.
+    /// This is cancel safe.
+    pub(super) async fn wait(&mut self) -> Vec<StyledGraphemes> {
+        match (&mut self.handle).await {
+            Ok(Ok(lines)) => lines,
+            Ok(Err(e)) => vec![StyledGraphemes::from(e.to_string())],
+            Err(e) => vec![StyledGraphemes::from(e.to_string())],
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use rayon::prelude::*;
+use tokio::task::JoinHandle;
+
+use super::{fuzzy, Matcher};
+
+/// Number of items fuzzy matched between checks for cancellation.
+const CHUNK_SIZE: usize = 4096;
+
+/// A search running on the blocking thread pool.
+struct Job {
+    query: String,
+    /// Number of items the search covers.
+    len: usize,
+    cancelled: Arc<AtomicBool>,
+    handle: JoinHandle<Option<anyhow::Result<Vec<usize>>>>,
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Holds the items of a [`QuerySelector`](super::QuerySelector)
+/// and the matches for the latest input.
+///
+/// Searches run in the background; the fuzzy matcher runs in parallel over chunks of the items.
+/// Starting a new search cancels the one in progress,
+/// and an input extending the previous one is matched against
+/// the previous matches only, instead of all the items.
+/// Items can be added at any time; those added during a search
+/// are matched against the input once the search completes.
+pub struct ItemStore {
+    items: Arc<Vec<String>>,
+    /// Items added during a search.
+    incoming: Vec<String>,
+    /// The input `matches` are for.
+    query: String,
+    /// Indices of the matched items, in the order they are listed.
+    matches: Arc<Vec<usize>>,
+    /// Number of items `matches` covers, i.e. the items after it are yet to be matched.
+    matched_len: usize,
+    /// The matcher of the latest search, to match the added items with.
+    matcher: Option<Matcher>,
+    job: Option<Job>,
+}
+
+impl<T: std::fmt::Display> FromIterator<T> for ItemStore {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<String> = iter.into_iter().map(|item| item.to_string()).collect();
+        Self {
+            matches: Arc::new((0..items.len()).collect()),
+            matched_len: items.len(),
+            items: Arc::new(items),
+            incoming: Vec::new(),
+            query: String::new(),
+            matcher: None,
+            job: None,
+        }
+    }
+}
+
+impl ItemStore {
+    /// Returns all the items.
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Returns the input the current matches are for.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the number of matched items.
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// Returns the number of all the items, including those yet to be matched.
+    pub fn total(&self) -> usize {
+        self.items.len() + self.incoming.len()
+    }
+
+    /// Returns the matched item at the given position in the listing.
+    pub fn get(&self, position: usize) -> Option<&str> {
+        self.matches
+            .get(position)
+            .map(|&index| self.items[index].as_str())
+    }
+
+    /// Returns the index in [`ItemStore::items`] of the matched item
+    /// at the given position in the listing.
+    pub fn index(&self, position: usize) -> Option<usize> {
+        self.matches.get(position).copied()
+    }
+
+    /// Returns the position in the listing of the item
+    /// at the given index in [`ItemStore::items`], if it matches.
+    pub fn position(&self, index: usize) -> Option<usize> {
+        self.matches.iter().position(|&matched| matched == index)
+    }
+
+    /// Returns whether a search is in progress.
+    pub fn is_searching(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Adds items, matching them against the current input in the background.
+    /// While the input is empty, they are listed as they are.
+    pub fn extend<I: IntoIterator<Item = String>>(&mut self, items: I) {
+        self.incoming.extend(items);
+        if self.job.is_none() {
+            self.flush();
+        }
+    }
+
+    /// Starts searching the items for `query` in the background,
+    /// cancelling the search in progress.
+    /// The current matches stay until [`ItemStore::wait`] returns.
+    /// An empty `query` lists all the items at once.
+    ///
+    /// `narrowing` tells whether a `Filter` only drops more items as the input grows,
+    /// like a substring filter; the fuzzy matcher knows it by itself.
+    pub fn search(&mut self, query: &str, matcher: Matcher, narrowing: bool) {
+        if self.job.as_ref().is_some_and(|job| job.query == query) {
+            return;
+        }
+        self.job = None;
+        self.matcher = Some(matcher);
+        self.append_incoming();
+
+        if query.is_empty() {
+            self.query.clear();
+            self.matches = Arc::new((0..self.items.len()).collect());
+            self.matched_len = self.items.len();
+            return;
+        }
+        if self.query == query && self.matched_len == self.items.len() {
+            return;
+        }
+
+        let narrows = self.query == query
+            || match matcher {
+                Matcher::Filter(_) => narrowing && query.starts_with(&self.query),
+                Matcher::Fuzzy => fuzzy::narrows(&self.query, query),
+            };
+        self.spawn(query, narrows, matcher);
+    }
+
+    /// Waits for the search in progress and replaces the matches with its result.
+    /// Returns `None` if no search is in progress.
+    ///
+    /// This is cancel safe: if the returned future is dropped,
+    /// the search keeps running and can be waited for again.
+    pub async fn wait(&mut self) -> Option<anyhow::Result<()>> {
+        let job = self.job.as_mut()?;
+        let ret = (&mut job.handle).await;
+        let query = std::mem::take(&mut job.query);
+        let len = job.len;
+        self.job = None;
+        Some(match ret {
+            Ok(Some(Ok(matches))) => {
+                self.query = query;
+                self.matches = Arc::new(matches);
+                self.matched_len = len;
+                self.flush();
+                Ok(())
+            }
+            Ok(Some(Err(e))) => Err(e),
+            // The job is cancelled only when it is replaced or dropped.
+            Ok(None) => Ok(()),
+            Err(e) => Err(e.into()),
+        })
+    }
+
+    fn append_incoming(&mut self) {
+        if !self.incoming.is_empty() {
+            Arc::make_mut(&mut self.items).append(&mut self.incoming);
+        }
+    }
+
+    /// Appends the incoming items and matches them against the current input.
+    fn flush(&mut self) {
+        self.append_incoming();
+        if self.matched_len == self.items.len() {
+            return;
+        }
+        match self.matcher {
+            Some(matcher) if !self.query.is_empty() => {
+                let query = self.query.clone();
+                self.spawn(&query, true, matcher);
+            }
+            _ => {
+                Arc::make_mut(&mut self.matches).extend(self.matched_len..self.items.len());
+                self.matched_len = self.items.len();
+            }
+        }
+    }
+
+    /// Spawns a search for `query` over the current matches and the items yet to be matched
+    /// if `narrows`, or over all the items otherwise.
+    fn spawn(&mut self, query: &str, narrows: bool, matcher: Matcher) {
+        let candidates: Vec<usize> = if narrows {
+            self.matches
+                .iter()
+                .copied()
+                .chain(self.matched_len..self.items.len())
+                .collect()
+        } else {
+            (0..self.items.len()).collect()
+        };
+        let items = self.items.clone();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let query = query.to_string();
+            let cancelled = cancelled.clone();
+            tokio::task::spawn_blocking(move || {
+                search(&items, &candidates, &query, matcher, &cancelled)
+            })
+        };
+        self.job = Some(Job {
+            query: query.to_string(),
+            len: self.items.len(),
+            cancelled,
+            handle,
+        });
+    }
+}
+
+/// Matches the candidates (indices of `items`) against `query`,
+/// returning `None` if cancelled.
+fn search(
+    items: &[String],
+    candidates: &[usize],
+    query: &str,
+    matcher: Matcher,
+    cancelled: &AtomicBool,
+) -> Option<anyhow::Result<Vec<usize>>> {
+    match matcher {
+        Matcher::Filter(filter) => {
+            let strings: Vec<String> = candidates.iter().map(|&i| items[i].clone()).collect();
+            let kept = filter(query, &strings);
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            // Map the kept items back to their indices, in the order the filter returned.
+            let mut indices: HashMap<&str, VecDeque<usize>> = HashMap::new();
+            for &i in candidates {
+                indices.entry(items[i].as_str()).or_default().push_back(i);
+            }
+            Some(
+                kept.iter()
+                    .map(|item| {
+                        indices
+                            .get_mut(item.as_str())
+                            .and_then(VecDeque::pop_front)
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("the filter returned an item not given: {item}")
+                            })
+                    })
+                    .collect(),
+            )
+        }
+        Matcher::Fuzzy => {
+            let query = fuzzy::Query::parse(query);
+            let mut scored = candidates
+                .par_chunks(CHUNK_SIZE)
+                .map(|chunk| {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    Some(
+                        chunk
+                            .iter()
+                            .filter_map(|&i| {
+                                query
+                                    .find(&items[i])
+                                    .map(|(score, _)| (score, items[i].chars().count(), i))
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Option<Vec<_>>>()?
+                .concat();
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            // Sort by the original order as well,
+            // as the candidates are in the order of the previous input.
+            if query.ranks() {
+                scored.par_sort_unstable_by(|a, b| {
+                    b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2))
+                });
+            } else {
+                scored.par_sort_unstable_by_key(|(_, _, i)| *i);
+            }
+            Some(Ok(scored.into_iter().map(|(_, _, i)| i).collect()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTAINS: Matcher = Matcher::Filter(|query, items| {
+        items
+            .iter()
+            .filter(|item| item.contains(query))
+            .cloned()
+            .collect()
+    });
+
+    fn matches(store: &ItemStore) -> Vec<&str> {
+        (0..store.len()).filter_map(|i| store.get(i)).collect()
+    }
+
+    mod search {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_with_filter() {
+            let mut store: ItemStore = ["apple", "banana", "apple", "cherry"].into_iter().collect();
+            store.search("an", CONTAINS, true);
+            assert!(store.wait().await.unwrap().is_ok());
+            assert_eq!(vec!["banana"], matches(&store));
+
+            store.search("a", CONTAINS, true);
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(vec!["apple", "banana", "apple"], matches(&store));
+            assert!(store.wait().await.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_with_ranking_filter() {
+            // Sorts all the items, not only those of a chunk.
+            let sorted = Matcher::Filter(|_, items| {
+                let mut items = items.clone();
+                items.sort();
+                items
+            });
+            let mut store: ItemStore = (0..10_000).rev().map(|i| format!("{i:05}")).collect();
+            store.search("0", sorted, false);
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(Some("00000"), store.get(0));
+            assert_eq!(Some("09999"), store.get(9_999));
+        }
+
+        #[tokio::test]
+        async fn test_with_transforming_filter() {
+            let upper =
+                Matcher::Filter(|_, items| items.iter().map(|i| i.to_uppercase()).collect());
+            let mut store: ItemStore = ["a"].into_iter().collect();
+            store.search("a", upper, false);
+            assert!(store.wait().await.unwrap().is_err());
+        }
+
+        #[tokio::test]
+        async fn test_with_fuzzy() {
+            let mut store: ItemStore = (0..100_000).map(|i| format!("item-{i}")).collect();
+            store.search("99", Matcher::Fuzzy, false);
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(Some("item-99"), store.get(0));
+
+            // Narrowed from the matches of `99`.
+            store.search("999", Matcher::Fuzzy, false);
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(Some("item-999"), store.get(0));
+            let expected = (0..100_000)
+                .map(|i| format!("item-{i}"))
+                .filter(|item| fuzzy::find("999", item).is_some())
+                .count();
+            assert_eq!(expected, store.len());
+        }
+
+        #[tokio::test]
+        async fn test_with_cancellation() {
+            let mut store: ItemStore = (0..100_000).map(|i| format!("item-{i}")).collect();
+            store.search("1", Matcher::Fuzzy, false);
+            let cancelled = store.job.as_ref().unwrap().cancelled.clone();
+            store.search("2", Matcher::Fuzzy, false);
+            assert!(cancelled.load(Ordering::Relaxed));
+
+            store.wait().await.unwrap().unwrap();
+            assert_eq!("2", store.query());
+            assert_eq!(Some("item-2"), store.get(0));
+        }
+
+        #[tokio::test]
+        async fn test_with_incoming_items() {
+            let mut store: ItemStore = ["apple", "banana"].into_iter().collect();
+            store.extend(["avocado".to_string()]);
+            assert_eq!(vec!["apple", "banana", "avocado"], matches(&store));
+
+            store.search("an", CONTAINS, true);
+            // Added during the search, and matched after it.
+            store.extend(["mango".to_string(), "cherry".to_string()]);
+            assert_eq!(5, store.total());
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(vec!["banana"], matches(&store));
+            store.wait().await.unwrap().unwrap();
+            assert_eq!(vec!["banana", "mango"], matches(&store));
+
+            // Narrowed from the matches including the added items.
+            store.search("ang", CONTAINS, true);
+            store.extend(["tangerine".to_string()]);
+            store.search("ango", CONTAINS, true);
+            while let Some(searched) = store.wait().await {
+                searched.unwrap();
+            }
+            assert_eq!(vec!["mango"], matches(&store));
+            assert_eq!(6, store.items().len());
+        }
+
+        #[tokio::test]
+        async fn test_with_same_query() {
+            let mut store: ItemStore = ["a", "b"].into_iter().collect();
+            store.search("a", Matcher::Fuzzy, false);
+            store.search("", Matcher::Fuzzy, false);
+            assert!(!store.is_searching());
+            assert_eq!(vec!["a", "b"], matches(&store));
+        }
+    }
+}
@@ -1,32 +1,37 @@
 //! Offers functionality for reading input from the user.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range, sync::Arc};
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
+        grapheme::StyledGraphemes,
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
-    suggest::Suggest,
-    validate::{ErrorMessageGenerator, Validator, ValidatorManager},
+    preset::{self, EvaluateFuture, Evaluator},
+    suggest::{Candidate, Completer, Suggest},
+    validate::{AsyncValidatorManager, Rule, ValidateFuture, ValidatorManager},
     widgets::{
         listbox::{self, Listbox},
+        spinner::{self, Spinner},
         text::{self, Text},
-        text_editor::{self, History},
+        text_editor::{self, History, HistoryFile},
     },
     Signal,
 };
 
 pub mod evaluate;
+pub mod search;
+pub mod vi;
 
 /// Represents the indices of various components in the readline preset.
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Index {
     Title = 0,
     Readline = 1,
@@ -34,11 +39,60 @@ pub enum Index {
     ErrorMessage = 3,
 }
 
+/// Key bindings used by the default evaluator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Keymap {
+    #[default]
+    /// Emacs-style bindings, see [`evaluate::readline`].
+    Emacs,
+    /// Vi-style bindings with insert and normal modes, see [`vi`].
+    Vi,
+}
+
+/// The part of the input completed by the suggestions on <kbd>Tab</kbd>.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompletionScope {
+    #[default]
+    /// The whole input is matched against the suggestions and replaced.
+    Line,
+    /// Only the word at the cursor, delimited by `word_break_chars`,
+    /// is matched up to the cursor and replaced, e.g. `fea` in `git checkout fea`.
+    Word,
+}
+
 /// Represents the focus state of the readline,
 /// determining which component is currently active for input handling.
 pub enum Focus {
     Readline,
     Suggestion,
+    HistorySearch,
+}
+
+/// An asynchronous validation of the submitted text.
+struct Validation {
+    /// The text being validated.
+    text: String,
+    /// Resolves to whether `text` is valid.
+    future: ValidateFuture<'static>,
+}
+
+impl Validation {
+    fn new(validator: Arc<AsyncValidatorManager<str>>, text: String) -> Self {
+        let input = text.clone();
+        Self {
+            text,
+            future: Box::pin(async move { validator.validate(&input).await }),
+        }
+    }
+}
+
+/// Spinner state that stays active until the validation resolves.
+struct Validating;
+
+impl spinner::State for Validating {
+    async fn is_idle(&self) -> bool {
+        false
+    }
 }
 
 /// `Readline` struct provides functionality
@@ -50,18 +104,51 @@ pub struct Readline {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// Holds the focus state for event handling, determining which component is currently focused.
     pub focus: Focus,
     /// Holds a title's renderer state, used for rendering the title section.
     pub title: text::State,
     /// Holds a text editor's renderer state, used for rendering the text input area.
     pub readline: text_editor::State,
-    /// Optional suggest component for autocomplete functionality.
-    pub suggest: Option<Suggest>,
+    /// Key bindings used by the default evaluator.
+    pub keymap: Keymap,
+    /// State of the vi key bindings, used when `keymap` is `Keymap::Vi`.
+    pub vi: vi::Vi,
+    /// Optional file the history is loaded from on each prompt and appended to on submit.
+    pub history_file: Option<HistoryFile>,
+    /// State of the incremental history search, used while `focus` is `Focus::HistorySearch`.
+    pub history_search: search::HistorySearch,
+    /// Optional completer providing the candidates for autocomplete functionality.
+    pub completer: Option<Box<dyn Completer>>,
+    /// The candidates shown in the suggestion box.
+    candidates: Vec<Candidate>,
+    /// The candidates of the last completion of the whole input,
+    /// offered as autosuggestions until the prompt ends.
+    last_completions: Vec<String>,
+    /// Style for the descriptions of the candidates.
+    pub description_style: ContentStyle,
+    /// Whether to show the most recent matching history entry or suggestion
+    /// as an autosuggestion after the cursor.
+    pub autosuggestion: bool,
+    /// The part of the input completed by the suggestions.
+    pub completion_scope: CompletionScope,
+    /// The range of the text replaced by the selected suggestion,
+    /// or `None` to replace the whole input.
+    completion_range: Option<Range<usize>>,
     /// Holds a suggest box's renderer state, used when rendering suggestions for autocomplete.
     pub suggestions: listbox::State,
     /// Optional validator manager for input validation.
     pub validator: Option<ValidatorManager<str>>,
+    /// Optional asynchronous validator manager, run after `validator` passes.
+    pub async_validator: Option<Arc<AsyncValidatorManager<str>>>,
+    /// The asynchronous validation started on Enter, if it is still pending.
+    validation: Option<Validation>,
+    /// Spinner shown in place of the error message while the asynchronous validation runs.
+    pub validation_spinner: Spinner,
+    /// Whether to run `validator` on every edit instead of only on Enter.
+    pub live_validation: bool,
     /// Holds an error message's renderer state, used for rendering error messages.
     pub error_message: text::State,
 }
@@ -70,7 +157,8 @@ impl Default for Readline {
     fn default() -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             focus: Focus::Readline,
             title: text::State {
                 config: text::config::Config {
@@ -85,6 +173,8 @@ impl Default for Readline {
             readline: text_editor::State {
                 texteditor: Default::default(),
                 history: Default::default(),
+                kill_ring: Default::default(),
+                autosuggestion: Default::default(),
                 config: text_editor::config::Config {
                     prefix: String::from("❯❯ "),
                     mask: Default::default(),
@@ -97,12 +187,30 @@ impl Default for Readline {
                         ..Default::default()
                     },
                     inactive_char_style: ContentStyle::default(),
+                    autosuggestion_style: ContentStyle {
+                        foreground_color: Some(Color::DarkGrey),
+                        ..Default::default()
+                    },
                     edit_mode: Default::default(),
                     word_break_chars: HashSet::from([' ']),
                     lines: Default::default(),
                 },
             },
-            suggest: Default::default(),
+            keymap: Default::default(),
+            vi: Default::default(),
+            history_file: Default::default(),
+            history_search: Default::default(),
+            completer: Default::default(),
+            candidates: Default::default(),
+            last_completions: Default::default(),
+            description_style: ContentStyle {
+                foreground_color: Some(Color::DarkGrey),
+                attributes: Attributes::from(Attribute::Italic),
+                ..Default::default()
+            },
+            autosuggestion: false,
+            completion_scope: Default::default(),
+            completion_range: None,
             suggestions: listbox::State {
                 listbox: Listbox::from(Vec::<String>::new()),
                 config: listbox::config::Config {
@@ -120,6 +228,10 @@ impl Default for Readline {
                 },
             },
             validator: Default::default(),
+            async_validator: Default::default(),
+            validation: None,
+            validation_spinner: Spinner::default().suffix("Validating..."),
+            live_validation: false,
             error_message: text::State {
                 text: Default::default(),
                 config: text::config::Config {
@@ -138,15 +250,27 @@ impl Default for Readline {
 #[async_trait::async_trait]
 impl crate::Prompt for Readline {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        // Drop the validation left pending by a prompt interrupted with Ctrl+C.
+        self.validation = None;
+
+        // Reload the history to merge the entries appended by other sessions.
+        // An unreadable file must not keep the prompt from running.
+        if let Some(file) = &self.history_file {
+            match file.load_history() {
+                Ok(history) => self.readline.history = Some(history),
+                Err(_) => {
+                    self.readline.history.get_or_insert_with(History::default);
+                }
+            }
+        }
+
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 [
                     (Index::Title, self.title.create_graphemes(size.0, size.1)),
-                    (
-                        Index::Readline,
-                        self.readline.create_graphemes(size.0, size.1),
-                    ),
+                    (Index::Readline, self.readline_graphemes(size.0, size.1)),
                     (
                         Index::Suggestion,
                         self.suggestions.create_graphemes(size.0, size.1),
@@ -156,6 +280,7 @@ impl crate::Prompt for Readline {
                         self.error_message.create_graphemes(size.0, size.1),
                     ),
                 ],
+                [(Index::Readline, PaneConstraint::default().priority(1))],
                 true,
             )
             .await?,
@@ -164,22 +289,90 @@ impl crate::Prompt for Readline {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        // Store the previous text in the readline before evaluating the event.
+        let prev = self.readline.texteditor.text_without_cursor().to_string();
+
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+
+        // Hide the autosuggestion once the prompt ends.
+        self.readline.autosuggestion = match ret {
+            Ok(Signal::Continue) => self.find_autosuggestion(),
+            _ => None,
+        };
+
+        let text = self.readline.texteditor.text_without_cursor().to_string();
+        // Editing the text cancels its pending validation.
+        if self
+            .validation
+            .as_ref()
+            .is_some_and(|validation| validation.text != text)
+        {
+            self.validation = None;
+            self.error_message.text = Text::default();
+        }
+
+        // If the text in the readline has changed, validate it right away.
+        if self.live_validation && prev != text {
+            if let Some(validator) = &self.validator {
+                self.error_message.text = if validator.validate(&text) {
+                    Text::default()
+                } else {
+                    Text::from(validator.generate_error_message(&text))
+                };
+            }
+        }
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
 
+    /// Waits for the asynchronous validation started on Enter,
+    /// showing the validation spinner in place of the error message,
+    /// and submits the text if it is valid.
+    async fn background(&mut self) -> Option<anyhow::Result<Signal>> {
+        let validation = self.validation.as_mut()?;
+        let valid = match &self.renderer {
+            Some(renderer) => {
+                tokio::select! {
+                    valid = &mut validation.future => valid,
+                    // The spinner only returns when rendering fails.
+                    ret = spinner::run(
+                        &self.validation_spinner,
+                        Validating,
+                        Index::ErrorMessage,
+                        renderer.clone(),
+                    ) => return Some(ret.map(|()| Signal::Continue)),
+                }
+            }
+            None => (&mut validation.future).await,
+        };
+        Some(self.finish_validation(valid).await)
+    }
+
     type Return = String;
 
     fn finalize(&mut self) -> anyhow::Result<Self::Return> {
         let ret = self.readline.texteditor.text_without_cursor().to_string();
+        self.validation = None;
 
         // Reset the text editor state for the next prompt.
-        self.readline.texteditor.erase_all();
+        self.readline.texteditor = Default::default();
+        self.vi.reset();
+        if let (Focus::HistorySearch, Some(history)) = (&self.focus, &mut self.readline.history) {
+            history.move_to_tail();
+        }
+        self.focus = Focus::Readline;
+        self.suggestions.listbox = Listbox::from(Vec::<String>::new());
+        self.candidates.clear();
+        self.last_completions.clear();
+        self.completion_range = None;
 
         Ok(ret)
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Readline {
@@ -196,8 +389,27 @@ impl Readline {
     }
 
     /// Enables suggestion functionality with the provided `Suggest` instance.
-    pub fn enable_suggest(mut self, suggest: Suggest) -> Self {
-        self.suggest = Some(suggest);
+    pub fn enable_suggest(self, suggest: Suggest) -> Self {
+        self.completer(suggest)
+    }
+
+    /// Sets the completer providing the candidates on <kbd>Tab</kbd>,
+    /// e.g. a closure of `Fn(&Context) -> Vec<Candidate>` or an asynchronous [`Completer`].
+    pub fn completer<C: Completer + 'static>(mut self, completer: C) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    /// Sets the style for the descriptions shown next to the candidates.
+    pub fn description_style(mut self, style: ContentStyle) -> Self {
+        self.description_style = style;
+        self
+    }
+
+    /// Sets the part of the input completed by the suggestions,
+    /// e.g. `CompletionScope::Word` for command-style input.
+    pub fn completion_scope(mut self, scope: CompletionScope) -> Self {
+        self.completion_scope = scope;
         self
     }
 
@@ -207,6 +419,36 @@ impl Readline {
         self
     }
 
+    /// Enables history persisted to the given file,
+    /// e.g. `HistoryFile::new(path).ignore_space(true)`.
+    /// The file is reloaded on each prompt, and submitted inputs are appended to it.
+    /// Both are best-effort: failing to read or write the file does not fail the prompt.
+    pub fn history_file(mut self, file: HistoryFile) -> Self {
+        self.history_file = Some(file);
+        self
+    }
+
+    /// Sets the style for the matching part of the entries in the history search.
+    pub fn history_search_style(mut self, style: ContentStyle) -> Self {
+        self.history_search.highlight_style = style;
+        self
+    }
+
+    /// Enables the autosuggestion, showing the rest of the most recent history entry
+    /// (or else the first candidate of the last <kbd>Tab</kbd> with `CompletionScope::Line`)
+    /// that starts with the input after the cursor.
+    /// <kbd>→</kbd>, <kbd>End</kbd> or <kbd>Ctrl + F</kbd> accepts it.
+    pub fn enable_autosuggestion(mut self) -> Self {
+        self.autosuggestion = true;
+        self
+    }
+
+    /// Sets the style for the autosuggestion.
+    pub fn autosuggestion_style(mut self, style: ContentStyle) -> Self {
+        self.readline.config.autosuggestion_style = style;
+        self
+    }
+
     /// Sets the prefix string displayed before the input text.
     pub fn prefix<T: AsRef<str>>(mut self, prefix: T) -> Self {
         self.readline.config.prefix = prefix.as_ref().to_string();
@@ -243,6 +485,18 @@ impl Readline {
         self
     }
 
+    /// Sets the key bindings, e.g. `Keymap::Vi` for vi-style editing.
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Sets the prefix shown in vi normal mode in place of the usual prefix.
+    pub fn normal_mode_prefix<T: AsRef<str>>(mut self, prefix: T) -> Self {
+        self.vi.normal_prefix = prefix.as_ref().to_string();
+        self
+    }
+
     /// Sets the characters to be for word break.
     pub fn word_break_chars(mut self, characters: HashSet<char>) -> Self {
         self.readline.config.word_break_chars = characters;
@@ -256,32 +510,197 @@ impl Readline {
     }
 
     /// Sets the function to evaluate the input, allowing for custom evaluation logic.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
         self
     }
 
     /// Configures a validator for the input with a function to validate the input and another to configure the error message.
-    pub fn validator(
-        mut self,
-        validator: Validator<str>,
-        error_message_generator: ErrorMessageGenerator<str>,
-    ) -> Self {
+    pub fn validator<V, E>(mut self, validator: V, error_message_generator: E) -> Self
+    where
+        V: Fn(&str) -> bool + Send + Sync + 'static,
+        E: Fn(&str) -> String + Send + Sync + 'static,
+    {
         self.validator = Some(ValidatorManager::new(validator, error_message_generator));
         self
     }
 
+    /// Configures a validation rule for the input, e.g. `Rule::non_empty().and(Rule::max_len(20))`.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.validator = Some(rule.into());
+        self
+    }
+
+    /// Enables validation on every edit, so the error message is updated
+    /// (and cleared once the input becomes valid) before Enter is pressed.
+    ///
+    /// Only the synchronous validator runs live; the asynchronous one still runs on Enter.
+    pub fn live_validation(mut self, enabled: bool) -> Self {
+        self.live_validation = enabled;
+        self
+    }
+
+    /// Configures an asynchronous validator for the input,
+    /// which runs on Enter after the synchronous validator passes.
+    /// A spinner is shown while the validation is pending.
+    pub fn async_validator<V, E>(mut self, validator: V, error_message_generator: E) -> Self
+    where
+        V: for<'a> Fn(&'a str) -> ValidateFuture<'a> + Send + Sync + 'static,
+        E: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.async_validator = Some(Arc::new(AsyncValidatorManager::new(
+            validator,
+            error_message_generator,
+        )));
+        self
+    }
+
+    /// Sets the spinner shown while the asynchronous validation is pending.
+    pub fn validation_spinner(mut self, spinner: Spinner) -> Self {
+        self.validation_spinner = spinner;
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
+        self
+    }
+
+    /// Returns the rest of the most recent history entry,
+    /// or else the first candidate of the last completion, that starts with the input.
+    /// Only the end of the input is completed, and not while searching or in vi normal mode.
+    /// The completer is not run for it, since it may run commands or read the file system.
+    /// Accepts the valid `text`: records it in the history and ends the prompt.
+    fn submit(&mut self, text: &str) -> Signal {
+        if let Some(history) = &mut self.readline.history {
+            history.insert(text);
+        }
+        // Failing to save the history must not lose the valid input.
+        if let Some(file) = &self.history_file {
+            file.append(text).ok();
+        }
+        // For representing the end of the prompt,
+        // reset the style of the cursor to default.
+        self.readline.config.active_char_style = ContentStyle::default();
+        Signal::Quit
+    }
+
+    /// Applies the result of the pending asynchronous validation.
+    async fn finish_validation(&mut self, valid: bool) -> anyhow::Result<Signal> {
+        let Some(validation) = self.validation.take() else {
+            return Ok(Signal::Continue);
+        };
+        let signal = if valid {
+            self.error_message.text = Text::default();
+            self.readline.autosuggestion = None;
+            self.submit(&validation.text)
+        } else if let Some(validator) = &self.async_validator {
+            self.error_message.text =
+                Text::from(validator.generate_error_message(&validation.text));
+            Signal::Continue
+        } else {
+            Signal::Continue
+        };
+        let size = self.backend.size()?;
+        self.render(size.0, size.1).await?;
+        Ok(signal)
+    }
+
+    fn find_autosuggestion(&self) -> Option<String> {
+        let editing = match self.focus {
+            Focus::Readline => self.keymap == Keymap::Emacs || self.vi.mode == vi::Mode::Insert,
+            _ => false,
+        };
+        let text = self.readline.texteditor.text_without_cursor().to_string();
+        if !self.autosuggestion || !editing || text.is_empty() {
+            return None;
+        }
+        let entry = match self
+            .readline
+            .history
+            .as_ref()
+            .and_then(|history| history.latest_starting_with(&text))
+        {
+            Some(entry) => entry.to_string(),
+            None => self
+                .last_completions
+                .iter()
+                .find(|value| value.len() > text.len() && value.starts_with(&text))?
+                .clone(),
+        };
+        Some(entry[text.len()..].to_string())
+    }
+
+    /// Shows the candidates in the suggestion box,
+    /// with the descriptions aligned after the values.
+    fn set_candidates(&mut self, candidates: Vec<Candidate>) {
+        let width = candidates
+            .iter()
+            .map(|candidate| StyledGraphemes::from(candidate.value.as_str()).widths())
+            .max()
+            .unwrap_or_default();
+        let items = candidates
+            .iter()
+            .map(|candidate| {
+                let value = StyledGraphemes::from(candidate.value.as_str());
+                match &candidate.description {
+                    Some(description) => StyledGraphemes::from_iter([
+                        &value,
+                        &StyledGraphemes::from(" ".repeat(width - value.widths() + 2)),
+                        &StyledGraphemes::from_str(description, self.description_style),
+                    ]),
+                    None => value,
+                }
+            })
+            .collect();
+        self.suggestions.listbox = Listbox::from_styled_graphemes(items);
+        self.candidates = candidates;
+    }
+
+    /// Creates the graphemes of the text editor,
+    /// with the normal mode prefix as the indicator in vi normal mode,
+    /// or the search indicator and the highlighted entry while searching the history.
+    fn readline_graphemes(&mut self, width: u16, height: u16) -> StyledGraphemes {
+        if let Focus::HistorySearch = self.focus {
+            let config = &self.readline.config;
+            let text = match config.mask {
+                Some(mask) => self.readline.texteditor.masking(mask),
+                None => self.history_search.highlight(
+                    self.readline
+                        .texteditor
+                        .text_without_cursor()
+                        .apply_style(config.inactive_char_style),
+                ),
+            };
+            let buf = StyledGraphemes::from_iter([
+                &StyledGraphemes::from_str(self.history_search.indicator(), config.prefix_style),
+                &text,
+            ]);
+            let lines = config.lines.unwrap_or(height as usize).min(height as usize);
+            StyledGraphemes::from_lines(buf.wrapped_lines(width as usize).into_iter().take(lines))
+        } else if self.keymap == Keymap::Vi && self.vi.mode == vi::Mode::Normal {
+            std::mem::swap(&mut self.readline.config.prefix, &mut self.vi.normal_prefix);
+            let graphemes = self.readline.create_graphemes(width, height);
+            std::mem::swap(&mut self.readline.config.prefix, &mut self.vi.normal_prefix);
+            graphemes
+        } else {
+            self.readline.create_graphemes(width, height)
+        }
+    }
+
     /// Render the prompt with the specified width and height.
     async fn render(&mut self, width: u16, height: u16) -> anyhow::Result<()> {
+        let readline = self.readline_graphemes(width, height);
         match self.renderer.as_ref() {
             Some(renderer) => {
                 renderer
                     .update([
                         (Index::Title, self.title.create_graphemes(width, height)),
-                        (
-                            Index::Readline,
-                            self.readline.create_graphemes(width, height),
-                        ),
+                        (Index::Readline, readline),
                         (
                             Index::Suggestion,
                             self.suggestions.create_graphemes(width, height),
@@ -298,3 +717,515 @@ impl Readline {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        core::{
+            backend::Memory,
+            crossterm::event::{KeyCode, KeyModifiers},
+        },
+        suggest::{Context, PathCompleter},
+        test_util::{key, key_with, type_str},
+        Prompt,
+    };
+
+    mod evaluator {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_with_captured_state() {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let mut readline =
+                Readline::default()
+                    .backend(Memory::new(20, 5))
+                    .evaluator(move |event, ctx| {
+                        let tx = tx.clone();
+                        Box::pin(async move {
+                            tx.send(event.clone()).ok();
+                            evaluate::default(event, ctx).await
+                        })
+                    });
+
+            let mut events = type_str("ab");
+            events.push(key(KeyCode::Enter));
+            assert_eq!("ab", readline.run_with_events(events).await.unwrap());
+
+            let mut received = 0;
+            while rx.try_recv().is_ok() {
+                received += 1;
+            }
+            assert_eq!(3, received);
+        }
+    }
+
+    mod background {
+        use super::*;
+
+        /// A readline whose asynchronous validator never resolves.
+        async fn hung_readline() -> Readline {
+            let mut readline = Readline::default()
+                .async_validator(
+                    |_| Box::pin(std::future::pending()),
+                    |_| String::from("unreachable"),
+                )
+                .backend(Memory::new(40, 5));
+            readline.initialize().await.unwrap();
+            for event in type_str("name\n") {
+                readline.evaluate(&event).await.unwrap();
+            }
+            readline
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_cancel_on_edit() {
+            let mut readline = hung_readline().await;
+            let pending = std::time::Duration::from_secs(1);
+            assert!(tokio::time::timeout(pending, readline.background())
+                .await
+                .is_err());
+
+            readline.evaluate(&key(KeyCode::Backspace)).await.unwrap();
+            assert!(readline.background().await.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_cancel_on_ctrl_c() {
+            let mut readline = hung_readline().await;
+            assert!(readline
+                .evaluate(&key_with(KeyCode::Char('c'), KeyModifiers::CONTROL))
+                .await
+                .is_err());
+
+            // The next prompt does not resume the validation.
+            readline.initialize().await.unwrap();
+            assert!(readline.background().await.is_none());
+        }
+    }
+
+    mod run_with_events {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let memory = Memory::new(20, 5);
+            let mut readline = Readline::default().title("Name").backend(memory.clone());
+
+            let mut events = type_str("hello");
+            events.extend([key(KeyCode::Backspace), key(KeyCode::Enter)]);
+
+            assert_eq!("hell", readline.run_with_events(events).await.unwrap());
+            assert_eq!("Name", memory.lines()[0]);
+            assert_eq!("❯❯ hell", memory.lines()[1]);
+        }
+
+        #[tokio::test]
+        async fn test_with_insufficient_height() {
+            let memory = Memory::new(20, 1);
+            let mut readline = Readline::default().title("Name").backend(memory.clone());
+
+            let mut events = type_str("hi");
+            events.push(key(KeyCode::Enter));
+
+            assert_eq!("hi", readline.run_with_events(events).await.unwrap());
+            assert_eq!(vec!["❯❯ hi"], memory.lines());
+        }
+
+        #[tokio::test]
+        async fn test_with_undo_and_redo() {
+            let mut readline = Readline::default().backend(Memory::new(20, 5));
+
+            let ctrl = |ch| key_with(KeyCode::Char(ch), KeyModifiers::CONTROL);
+            let mut events = type_str("hello");
+            events.extend([
+                ctrl('w'),
+                ctrl('z'),
+                ctrl('7'),
+                key_with(KeyCode::Char('_'), KeyModifiers::ALT),
+                key(KeyCode::Enter),
+            ]);
+
+            assert_eq!("hello", readline.run_with_events(events).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_with_kill_and_yank() {
+            let mut readline = Readline::default().backend(Memory::new(20, 5));
+
+            let ctrl = |ch| key_with(KeyCode::Char(ch), KeyModifiers::CONTROL);
+            let mut events = type_str("hello");
+            events.extend([ctrl('a'), ctrl('k'), ctrl('y'), ctrl('y')]);
+            events.push(key(KeyCode::Enter));
+
+            assert_eq!(
+                "hellohello",
+                readline.run_with_events(events).await.unwrap()
+            );
+            // Ctrl+U kills to the start of the line only.
+            let mut events = type_str("hello world");
+            events.extend((0..5).map(|_| key(KeyCode::Left)));
+            events.extend([ctrl('u'), ctrl('e'), ctrl('y')]);
+            events.push(key(KeyCode::Enter));
+            assert_eq!(
+                "worldhello ",
+                readline.run_with_events(events).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_history_search() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default().enable_history().backend(memory.clone());
+            let history = readline.readline.history.as_mut().unwrap();
+            for entry in ["cargo build", "git status", "cargo test"] {
+                history.insert(entry);
+            }
+
+            let ctrl = |ch| key_with(KeyCode::Char(ch), KeyModifiers::CONTROL);
+            let mut events = vec![ctrl('r')];
+            events.extend(type_str("cargo"));
+            events.push(ctrl('r'));
+
+            readline.run_with_events(events).await.unwrap();
+            assert_eq!("(reverse-i-search)`cargo': cargo build", memory.lines()[0]);
+
+            // Cancel restores the text typed before the search.
+            let mut events = type_str("git");
+            events.extend([ctrl('r'), ctrl('r'), ctrl('g')]);
+            assert_eq!("git", readline.run_with_events(events).await.unwrap());
+
+            let mut events = vec![ctrl('r')];
+            events.extend(type_str("stat"));
+            events.extend([key(KeyCode::Char('x')), key(KeyCode::Backspace)]);
+            events.push(key(KeyCode::Enter));
+            assert_eq!(
+                "git status",
+                readline.run_with_events(events).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_autosuggestion() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .enable_history()
+                .enable_suggest(Suggest::from_iter(["git status"]))
+                .enable_autosuggestion()
+                .backend(memory.clone());
+            let history = readline.readline.history.as_mut().unwrap();
+            history.insert("cargo build");
+            history.insert("cargo test");
+
+            readline.run_with_events(type_str("car")).await.unwrap();
+            assert_eq!("❯❯ cargo test", memory.lines()[0]);
+
+            let mut events = type_str("cargo b");
+            events.extend([key(KeyCode::End), key(KeyCode::Enter)]);
+            assert_eq!(
+                "cargo build",
+                readline.run_with_events(events).await.unwrap()
+            );
+            assert_eq!("❯❯ cargo build", memory.lines()[0]);
+
+            // The completer is not run for the autosuggestion...
+            let mut events = type_str("g");
+            events.extend([key(KeyCode::Right), key(KeyCode::Enter)]);
+            assert_eq!("g", readline.run_with_events(events).await.unwrap());
+
+            // ...but the candidates of the last Tab are offered.
+            let ctrl_u = key_with(KeyCode::Char('u'), KeyModifiers::CONTROL);
+            let mut events = type_str("g");
+            events.extend([key(KeyCode::Tab), ctrl_u.clone(), ctrl_u]);
+            events.extend(type_str("g"));
+            events.extend([key(KeyCode::Right), key(KeyCode::Enter)]);
+            assert_eq!(
+                "git status",
+                readline.run_with_events(events).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_history_file() {
+            let path = std::env::temp_dir()
+                .join(format!("promkit-readline-history-{}", std::process::id()));
+            std::fs::remove_file(&path).ok();
+
+            let mut events = type_str("first");
+            events.push(key(KeyCode::Enter));
+            Readline::default()
+                .history_file(HistoryFile::new(&path))
+                .backend(Memory::new(20, 5))
+                .run_with_events(events)
+                .await
+                .unwrap();
+
+            // Another session sees the entry.
+            let events = [key(KeyCode::Up), key(KeyCode::Enter)];
+            assert_eq!(
+                "first",
+                Readline::default()
+                    .history_file(HistoryFile::new(&path))
+                    .backend(Memory::new(20, 5))
+                    .run_with_events(events)
+                    .await
+                    .unwrap()
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_with_unwritable_history_file() {
+            let path = std::env::temp_dir()
+                .join(format!("promkit-readline-missing-{}", std::process::id()))
+                .join("history");
+
+            let mut events = type_str("kept");
+            events.push(key(KeyCode::Enter));
+            assert_eq!(
+                "kept",
+                Readline::default()
+                    .history_file(HistoryFile::new(&path))
+                    .backend(Memory::new(20, 5))
+                    .run_with_events(events)
+                    .await
+                    .unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_line_completion() {
+            let mut readline = Readline::default()
+                .enable_suggest(Suggest::from_iter(["git commit", "git status"]))
+                .backend(Memory::new(40, 5));
+
+            // The text after the cursor is matched too.
+            let mut events = type_str("git st");
+            events.extend([key(KeyCode::Left), key(KeyCode::Left)]);
+            events.extend([key(KeyCode::Tab), key(KeyCode::Esc), key(KeyCode::Enter)]);
+            assert_eq!(
+                "git status",
+                readline.run_with_events(events).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_word_completion() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .enable_suggest(Suggest::from_iter(["feature-a", "feature-b", "main"]))
+                .completion_scope(CompletionScope::Word)
+                .backend(memory.clone());
+
+            let mut events = type_str("git checkout fea");
+            events.extend([key(KeyCode::Tab), key(KeyCode::Tab)]);
+            readline.run_with_events(events).await.unwrap();
+            assert_eq!("❯❯ git checkout feature-b", memory.lines()[0]);
+
+            // Only the word before the cursor is matched and replaced.
+            let mut events = type_str("git m -b");
+            events.extend([key(KeyCode::Left), key(KeyCode::Left), key(KeyCode::Left)]);
+            events.extend([key(KeyCode::Tab), key(KeyCode::Esc), key(KeyCode::Enter)]);
+            assert_eq!(
+                "git main -b",
+                readline.run_with_events(events).await.unwrap()
+            );
+
+            // A backslash only escapes the delimiter for completers asking for it.
+            let mut events = type_str("echo a\\ m");
+            events.extend([key(KeyCode::Tab), key(KeyCode::Esc), key(KeyCode::Enter)]);
+            assert_eq!(
+                "echo a\\ main",
+                readline.run_with_events(events).await.unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_path_completion() {
+            let base =
+                std::env::temp_dir().join(format!("promkit-readline-path-{}", std::process::id()));
+            std::fs::create_dir_all(base.join("my dir")).unwrap();
+
+            let mut readline = Readline::default()
+                .completer(PathCompleter::default().base(&base))
+                .backend(Memory::new(40, 5));
+            let mut events = type_str("my");
+            events.extend([key(KeyCode::Tab), key(KeyCode::Esc), key(KeyCode::Enter)]);
+            assert_eq!("my dir/", readline.run_with_events(events).await.unwrap());
+
+            // Escaped, the name stays one word of the command line.
+            let mut readline = Readline::default()
+                .completer(PathCompleter::default().base(&base).escape(true))
+                .completion_scope(CompletionScope::Word)
+                .backend(Memory::new(40, 5));
+            let mut events = type_str("ls my\\ d");
+            events.extend([key(KeyCode::Tab), key(KeyCode::Esc), key(KeyCode::Enter)]);
+            assert_eq!(
+                "ls my\\ dir/",
+                readline.run_with_events(events).await.unwrap()
+            );
+
+            std::fs::remove_dir_all(base).unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_with_failing_completer() {
+            struct Failing;
+
+            #[async_trait::async_trait]
+            impl Completer for Failing {
+                async fn complete(&self, _: &Context<'_>) -> anyhow::Result<Vec<Candidate>> {
+                    Err(anyhow::anyhow!("not a git repository"))
+                }
+            }
+
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .completer(Failing)
+                .backend(memory.clone());
+
+            let mut events = type_str("ma");
+            events.push(key(KeyCode::Tab));
+            events.extend(type_str("in"));
+            events.push(key(KeyCode::Enter));
+            assert_eq!("main", readline.run_with_events(events).await.unwrap());
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line == "not a git repository"));
+        }
+
+        #[tokio::test]
+        async fn test_with_completer() {
+            struct Branches;
+
+            #[async_trait::async_trait]
+            impl Completer for Branches {
+                async fn complete(&self, context: &Context<'_>) -> anyhow::Result<Vec<Candidate>> {
+                    tokio::task::yield_now().await;
+                    Ok([("main", "default"), ("feature-a", "wip")]
+                        .into_iter()
+                        .filter(|(branch, _)| branch.starts_with(context.query()))
+                        .map(|(branch, description)| {
+                            Candidate::new(branch).description(description)
+                        })
+                        .collect())
+                }
+            }
+
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .completer(Branches)
+                .completion_scope(CompletionScope::Word)
+                .backend(memory.clone());
+
+            let mut events = type_str("git checkout ");
+            events.push(key(KeyCode::Tab));
+            readline.run_with_events(events).await.unwrap();
+            assert_eq!(
+                vec![
+                    "❯❯ git checkout main",
+                    "❯ main       default",
+                    "  feature-a  wip",
+                    "",
+                    ""
+                ],
+                memory.lines()
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_validation_error() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .validator(
+                    |text| !text.is_empty(),
+                    |_| String::from("must not be empty"),
+                )
+                .backend(memory.clone());
+
+            let mut events = vec![key(KeyCode::Enter)];
+            events.extend(type_str("ok"));
+            events.push(key(KeyCode::Enter));
+
+            assert_eq!("ok", readline.run_with_events(events).await.unwrap());
+        }
+
+        // The paused clock lets the spinner draw before the validator's sleep elapses.
+        #[tokio::test(start_paused = true)]
+        async fn test_with_async_validator() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .async_validator(
+                    |text| {
+                        Box::pin(async move {
+                            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                            text != "taken"
+                        })
+                    },
+                    |text| format!("{text} is not available"),
+                )
+                .backend(memory.clone());
+
+            // The validation settles before the next event;
+            // the prompt keeps running after the rejection until the events run out.
+            let mut events = type_str("taken");
+            events.push(key(KeyCode::Enter));
+            events.push(key(KeyCode::Left));
+            assert_eq!("taken", readline.run_with_events(events).await.unwrap());
+            assert!(memory.output().contains("Validating..."));
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line == "taken is not available"));
+
+            // A valid text ends the prompt before the next event.
+            let mut events = type_str("free");
+            events.push(key(KeyCode::Enter));
+            events.extend(type_str("x"));
+            assert_eq!("free", readline.run_with_events(events).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn test_with_live_validation() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .rule(Rule::min_len(2).message("too short"))
+                .live_validation(true)
+                .backend(memory.clone());
+
+            readline.run_with_events(type_str("a")).await.unwrap();
+            assert!(memory.lines().iter().any(|line| line == "too short"));
+
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .rule(Rule::min_len(2).message("too short"))
+                .live_validation(true)
+                .backend(memory.clone());
+
+            readline.run_with_events(type_str("ab")).await.unwrap();
+            assert!(!memory.lines().iter().any(|line| line == "too short"));
+        }
+
+        #[tokio::test]
+        async fn test_shows_validation_error() {
+            let memory = Memory::new(40, 5);
+            let mut readline = Readline::default()
+                .validator(
+                    |text| !text.is_empty(),
+                    |_| String::from("must not be empty"),
+                )
+                .backend(memory.clone());
+
+            readline
+                .run_with_events([key(KeyCode::Enter)])
+                .await
+                .unwrap();
+            assert!(memory
+                .lines()
+                .iter()
+                .any(|line| line == "must not be empty"));
+        }
+    }
+}
@@ -1,11 +1,9 @@
 use promkit_widgets::{listbox::Listbox, text::Text, text_editor};
 
 use crate::{
-    core::crossterm::{
-        event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
-        style::ContentStyle,
-    },
-    preset::readline::{Focus, Readline},
+    core::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+    preset::readline::{search, vi, CompletionScope, Focus, Keymap, Readline, Validation},
+    suggest::Context,
     Signal,
 };
 
@@ -29,12 +27,19 @@ pub async fn default(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal
             match ctx.focus {
                 Focus::Readline => {
                     // Handle the readline input events.
-                    return readline(event, ctx).await;
+                    return match ctx.keymap {
+                        Keymap::Emacs => readline(event, ctx).await,
+                        Keymap::Vi => vi::readline(event, ctx).await,
+                    };
                 }
                 Focus::Suggestion => {
                     // Handle the suggestion input events.
                     return suggestion(event, ctx).await;
                 }
+                Focus::HistorySearch => {
+                    // Handle the history search input events.
+                    return search::evaluate(event, ctx).await;
+                }
             }
         }
     }
@@ -42,6 +47,26 @@ pub async fn default(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal
     Ok(Signal::Continue)
 }
 
+/// Replaces the text being completed, i.e. the whole input
+/// or the word recorded in `completion_range`, with the selected suggestion.
+fn complete(ctx: &mut Readline) {
+    let Some(candidate) = ctx
+        .candidates
+        .get(ctx.suggestions.listbox.position())
+        .map(|candidate| candidate.value.clone())
+    else {
+        return;
+    };
+    match ctx.completion_range.take() {
+        Some(range) => {
+            let start = range.start;
+            ctx.readline.texteditor.replace_range(range, &candidate);
+            ctx.completion_range = Some(start..ctx.readline.texteditor.position());
+        }
+        None => ctx.readline.texteditor.replace(&candidate),
+    }
+}
+
 /// Default key bindings for the text editor.
 ///
 /// | Key                    | Action
@@ -49,18 +74,26 @@ pub async fn default(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal
 /// | <kbd>Enter</kbd>       | Exit the editor if input is valid, otherwise show error message
 /// | <kbd>Ctrl + C</kbd>    | Interrupt the current operation
 /// | <kbd>←</kbd>           | Move the cursor one character to the left
-/// | <kbd>→</kbd>           | Move the cursor one character to the right
+/// | <kbd>→</kbd>           | Move the cursor one character to the right, or accept the autosuggestion at the end (also <kbd>Ctrl + F</kbd>)
 /// | <kbd>Ctrl + A</kbd>    | Move the cursor to the start of the line
 /// | <kbd>Ctrl + E</kbd>    | Move the cursor to the end of the line
+/// | <kbd>End</kbd>         | Move the cursor to the end of the line, or accept the autosuggestion at the end
 /// | <kbd>↑</kbd>           | Recall the previous entry from history
 /// | <kbd>↓</kbd>           | Recall the next entry from history
+/// | <kbd>Ctrl + R</kbd>    | Search the history backward incrementally, see [`search`]
+/// | <kbd>Ctrl + S</kbd>    | Search the history forward incrementally
 /// | <kbd>Backspace</kbd>   | Delete the character before the cursor
-/// | <kbd>Ctrl + U</kbd>    | Delete all characters in the current line
-/// | <kbd>Tab</kbd>         | Autocomplete the current input based on available suggestions
+/// | <kbd>Ctrl + U</kbd>    | Kill from the start of the line to the cursor
+/// | <kbd>Ctrl + K</kbd>    | Kill from the cursor to the end of the line
+/// | <kbd>Tab</kbd>         | Autocomplete the current input (or word, see [`CompletionScope`]) based on available suggestions
 /// | <kbd>Alt + B</kbd>     | Move the cursor to the previous nearest character within set (default: whitespace)
 /// | <kbd>Alt + F</kbd>     | Move the cursor to the next nearest character within set (default: whitespace)
-/// | <kbd>Ctrl + W</kbd>    | Erase to the previous nearest character within set (default: whitespace)
-/// | <kbd>Alt + D</kbd>     | Erase to the next nearest character within set (default: whitespace)
+/// | <kbd>Ctrl + W</kbd>    | Kill to the previous nearest character within set (default: whitespace)
+/// | <kbd>Alt + D</kbd>     | Kill to the next nearest character within set (default: whitespace)
+/// | <kbd>Ctrl + Y</kbd>    | Yank the most recently killed text
+/// | <kbd>Alt + Y</kbd>     | Replace the yanked text with the previous killed text
+/// | <kbd>Ctrl + Z</kbd>    | Undo the last edit (also <kbd>Ctrl + _</kbd>)
+/// | <kbd>Alt + _</kbd>     | Redo the last undone edit
 pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal> {
     match event {
         // Return the input text when the validation passes.
@@ -83,19 +116,18 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
                     valid
                 })
                 .unwrap_or(true);
-            return {
-                if valid {
-                    if let Some(ref mut history) = &mut ctx.readline.history {
-                        history.insert(text);
-                    }
-                    // For representing the end of the prompt,
-                    // reset the style of the cursor to default.
-                    ctx.readline.config.active_char_style = ContentStyle::default();
-                    Ok(Signal::Quit)
-                } else {
-                    Ok(Signal::Continue)
+            if !valid {
+                return Ok(Signal::Continue);
+            }
+            // The asynchronous validation runs in the background,
+            // so that Ctrl+C and further edits cancel it, see `Readline::background`.
+            if let Some(validator) = &ctx.async_validator {
+                if ctx.validation.as_ref().map(|validation| &validation.text) != Some(&text) {
+                    ctx.validation = Some(Validation::new(validator.clone(), text));
                 }
-            };
+                return Ok(Signal::Continue);
+            }
+            return Ok(ctx.submit(&text));
         }
 
         // Try to autocomplete
@@ -105,16 +137,49 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }) => {
-            if let Some(suggest) = &ctx.suggest {
-                let text = ctx.readline.texteditor.text_without_cursor().to_string();
-                if let Some(candidates) = suggest.prefix_search(text) {
-                    ctx.suggestions.listbox = Listbox::from(candidates);
-                    ctx.readline
-                        .texteditor
-                        .replace(&ctx.suggestions.listbox.get().to_string());
+            if let Some(completer) = &ctx.completer {
+                let texteditor = &ctx.readline.texteditor;
+                let text = texteditor.text_without_cursor();
+                // Byte offset of the given grapheme index.
+                let offset = |index: usize| text.iter().take(index).map(|g| g.as_str().len()).sum();
+                let range = match ctx.completion_scope {
+                    CompletionScope::Line => None,
+                    CompletionScope::Word => {
+                        Some(texteditor.word_range(
+                            &ctx.readline.config.word_break_chars,
+                            completer.escapes_word_breaks(),
+                        ))
+                    }
+                };
+                let line = text.to_string();
+                let context = Context {
+                    line: &line,
+                    // The whole line is matched whatever the cursor position.
+                    position: match ctx.completion_scope {
+                        CompletionScope::Line => line.len(),
+                        CompletionScope::Word => offset(texteditor.position()),
+                    },
+                    start: range.as_ref().map(|range| offset(range.start)).unwrap_or(0),
+                };
+                // A failing completer, e.g. for git branches outside a repository,
+                // only shows the error and keeps the input.
+                match completer.complete(&context).await {
+                    Ok(candidates) if !candidates.is_empty() => {
+                        if ctx.completion_scope == CompletionScope::Line {
+                            ctx.last_completions = candidates
+                                .iter()
+                                .map(|candidate| candidate.value.clone())
+                                .collect();
+                        }
+                        ctx.set_candidates(candidates);
+                        ctx.completion_range = range;
+                        complete(ctx);
 
-                    // Enter suggestion mode.
-                    ctx.focus = Focus::Suggestion;
+                        // Enter suggestion mode.
+                        ctx.focus = Focus::Suggestion;
+                    }
+                    Ok(_) => (),
+                    Err(e) => ctx.error_message.text = Text::from(e.to_string()),
                 }
             }
         }
@@ -133,8 +198,17 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             modifiers: KeyModifiers::NONE,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('f'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
         }) => {
-            ctx.readline.texteditor.forward();
+            let accepted = ctx.readline.accept_autosuggestion();
+            if !accepted {
+                ctx.readline.texteditor.forward();
+            }
         }
         Event::Key(KeyEvent {
             code: KeyCode::Char('a'),
@@ -148,6 +222,17 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
         }) => ctx.readline.texteditor.move_to_tail(),
+        Event::Key(KeyEvent {
+            code: KeyCode::End,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            let accepted = ctx.readline.accept_autosuggestion();
+            if !accepted {
+                ctx.readline.texteditor.move_to_tail();
+            }
+        }
 
         // Move cursor to the nearest character.
         Event::Key(KeyEvent {
@@ -182,7 +267,13 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             modifiers: KeyModifiers::CONTROL,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => ctx.readline.texteditor.erase_all(),
+        }) => ctx.readline.kill_to_head(),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('k'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => ctx.readline.kill_to_tail(),
 
         // Erase to the nearest character.
         Event::Key(KeyEvent {
@@ -190,20 +281,63 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             modifiers: KeyModifiers::CONTROL,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => ctx
-            .readline
-            .texteditor
-            .erase_to_previous_nearest(&ctx.readline.config.word_break_chars),
+        }) => ctx.readline.kill_to_previous_nearest(),
 
         Event::Key(KeyEvent {
             code: KeyCode::Char('d'),
             modifiers: KeyModifiers::ALT,
             kind: KeyEventKind::Press,
             state: KeyEventState::NONE,
-        }) => ctx
-            .readline
-            .texteditor
-            .erase_to_next_nearest(&ctx.readline.config.word_break_chars),
+        }) => ctx.readline.kill_to_next_nearest(),
+
+        // Yank the killed text.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('y'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.yank();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('y'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.yank_pop();
+        }
+
+        // Undo / redo.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        // Terminals send Ctrl + _ as 0x1F, which is read as Ctrl + 7.
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char('7'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.texteditor.undo();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('_'),
+            modifiers: KeyModifiers::ALT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.readline.texteditor.redo();
+        }
 
         // Choose history
         Event::Key(KeyEvent {
@@ -231,6 +365,20 @@ pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signa
             }
         }
 
+        // Search history
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => search::start(ctx, search::Direction::Reverse),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('s'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => search::start(ctx, search::Direction::Forward),
+
         // Input char.
         Event::Key(KeyEvent {
             code: KeyCode::Char(ch),
@@ -269,9 +417,7 @@ pub async fn suggestion(event: &Event, ctx: &mut Readline) -> anyhow::Result<Sig
             state: KeyEventState::NONE,
         }) => {
             ctx.suggestions.listbox.forward();
-            ctx.readline
-                .texteditor
-                .replace(&ctx.suggestions.listbox.get().to_string());
+            complete(ctx);
         }
 
         Event::Key(KeyEvent {
@@ -281,14 +427,13 @@ pub async fn suggestion(event: &Event, ctx: &mut Readline) -> anyhow::Result<Sig
             state: KeyEventState::NONE,
         }) => {
             ctx.suggestions.listbox.backward();
-            ctx.readline
-                .texteditor
-                .replace(&ctx.suggestions.listbox.get().to_string());
+            complete(ctx);
         }
 
         // Switch back to the readline input.
         _ => {
             ctx.suggestions.listbox = Listbox::from(Vec::<String>::new());
+            ctx.candidates.clear();
 
             ctx.focus = Focus::Readline;
         }
@@ -0,0 +1,219 @@
+//! Incremental search over the history, started with
+//! <kbd>Ctrl + R</kbd> (reverse) or <kbd>Ctrl + S</kbd> (forward).
+//!
+//! | Key                    | Action
+//! | :--------------------- | :-------------------------------------------
+//! | Characters             | Extend the query and jump to the nearest matching entry
+//! | <kbd>Backspace</kbd>   | Shorten the query
+//! | <kbd>Ctrl + R</kbd>    | Jump to the next older matching entry
+//! | <kbd>Ctrl + S</kbd>    | Jump to the next newer matching entry
+//! | <kbd>Ctrl + G</kbd>    | Cancel the search and restore the text (also <kbd>Esc</kbd>)
+//! | <kbd>Enter</kbd>       | Accept the matching entry and submit it
+//! | Other keys             | Accept the matching entry and handle the key as usual
+
+use crate::{
+    core::{
+        crossterm::{
+            event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers},
+            style::{Attribute, Attributes, Color, ContentStyle},
+        },
+        grapheme::StyledGraphemes,
+    },
+    preset::readline::{evaluate, vi, Focus, Keymap, Readline},
+    Signal,
+};
+
+/// Direction of the history search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Search older entries.
+    #[default]
+    Reverse,
+    /// Search newer entries.
+    Forward,
+}
+
+/// State of an incremental history search.
+#[derive(Clone)]
+pub struct HistorySearch {
+    /// Style applied to the occurrences of the query in the matching entry.
+    pub highlight_style: ContentStyle,
+    query: String,
+    direction: Direction,
+    /// Whether no entry matches the query.
+    failed: bool,
+    /// The text before the search started, restored on cancel.
+    original: String,
+}
+
+impl Default for HistorySearch {
+    fn default() -> Self {
+        Self {
+            highlight_style: ContentStyle {
+                foreground_color: Some(Color::DarkYellow),
+                attributes: Attributes::from(Attribute::Bold),
+                ..Default::default()
+            },
+            query: Default::default(),
+            direction: Default::default(),
+            failed: false,
+            original: Default::default(),
+        }
+    }
+}
+
+impl HistorySearch {
+    /// Returns the current query.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns the indicator shown in place of the prefix,
+    /// e.g. ``(reverse-i-search)`cargo': ``.
+    pub fn indicator(&self) -> String {
+        format!(
+            "({}{})`{}': ",
+            if self.failed { "failed " } else { "" },
+            match self.direction {
+                Direction::Reverse => "reverse-i-search",
+                Direction::Forward => "i-search",
+            },
+            self.query,
+        )
+    }
+
+    /// Returns `text` with the occurrences of the query highlighted.
+    pub fn highlight(&self, text: StyledGraphemes) -> StyledGraphemes {
+        text.clone()
+            .highlight(&self.query, self.highlight_style)
+            .unwrap_or(text)
+    }
+}
+
+/// Starts a search in the given direction, remembering the current text.
+/// Does nothing if the history is disabled.
+pub fn start(ctx: &mut Readline, direction: Direction) {
+    if ctx.readline.history.is_none() {
+        return;
+    }
+    ctx.history_search.query.clear();
+    ctx.history_search.direction = direction;
+    ctx.history_search.failed = false;
+    ctx.history_search.original = ctx.readline.texteditor.text_without_cursor().to_string();
+    ctx.focus = Focus::HistorySearch;
+}
+
+/// Jumps to the nearest entry matching the query,
+/// staying on the current entry if it still matches unless `skip_current` is set.
+fn search(ctx: &mut Readline, skip_current: bool) {
+    let Some(history) = &mut ctx.readline.history else {
+        return;
+    };
+    let search = &mut ctx.history_search;
+    if !skip_current && history.get().contains(&search.query) {
+        search.failed = false;
+        return;
+    }
+    let found = match search.direction {
+        Direction::Reverse => history.search_backward(&search.query),
+        Direction::Forward => history.search_forward(&search.query),
+    };
+    search.failed = !found;
+    if found {
+        let entry = history.get();
+        ctx.readline.texteditor.replace(&entry);
+        // Put the cursor on the first occurrence of the query.
+        if let Some(&position) = StyledGraphemes::from(entry.as_str())
+            .find_all(&search.query)
+            .first()
+        {
+            ctx.readline.texteditor.move_to(position);
+        }
+    }
+}
+
+/// Leaves the search, keeping the text of the matching entry.
+fn accept(ctx: &mut Readline) {
+    if let Some(history) = &mut ctx.readline.history {
+        history.move_to_tail();
+    }
+    ctx.focus = Focus::Readline;
+}
+
+/// Key bindings while searching the history, see the [module documentation](self).
+pub async fn evaluate(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal> {
+    match event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.history_search.direction = Direction::Reverse;
+            search(ctx, true);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('s'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.history_search.direction = Direction::Forward;
+            search(ctx, true);
+        }
+
+        // Cancel the search.
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('g'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            let original = std::mem::take(&mut ctx.history_search.original);
+            ctx.readline.texteditor.replace(&original);
+            accept(ctx);
+        }
+
+        // Edit the query.
+        Event::Key(KeyEvent {
+            code: KeyCode::Backspace,
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.history_search.query.pop();
+            search(ctx, false);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::NONE,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+        | Event::Key(KeyEvent {
+            code: KeyCode::Char(ch),
+            modifiers: KeyModifiers::SHIFT,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }) => {
+            ctx.history_search.query.push(*ch);
+            search(ctx, false);
+        }
+
+        // Accept the entry and handle the key in the readline.
+        _ => {
+            accept(ctx);
+            return match ctx.keymap {
+                Keymap::Emacs => evaluate::readline(event, ctx).await,
+                Keymap::Vi => vi::readline(event, ctx).await,
+            };
+        }
+    }
+    Ok(Signal::Continue)
+}
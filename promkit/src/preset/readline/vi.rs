@@ -0,0 +1,670 @@
+//! Vi-style key bindings for `Readline`.
+//!
+//! The prompt starts in insert mode, where the default key bindings apply.
+//! <kbd>Esc</kbd> switches to normal mode, which supports:
+//!
+//! | Key                       | Action
+//! | :------------------------ | :-------------------------------------------
+//! | <kbd>h</kbd> <kbd>l</kbd> | Move the cursor one character left / right
+//! | <kbd>w</kbd> <kbd>b</kbd> | Move to the start of the next / previous word
+//! | <kbd>e</kbd>              | Move to the end of the word
+//! | <kbd>0</kbd> <kbd>^</kbd> <kbd>$</kbd> | Move to the start / first non-blank / end of the line
+//! | <kbd>f</kbd> <kbd>t</kbd> <kbd>F</kbd> <kbd>T</kbd> + char | Move to (or till) the next / previous char
+//! | <kbd>d</kbd> <kbd>c</kbd> <kbd>y</kbd> + motion | Delete / change / yank the text covered by the motion (`dd`, `cc`, `yy` for the whole line)
+//! | <kbd>x</kbd> <kbd>X</kbd> <kbd>D</kbd> <kbd>C</kbd> <kbd>s</kbd> <kbd>S</kbd> | Shorthands for `dl`, `dh`, `d$`, `c$`, `cl`, `cc`
+//! | <kbd>p</kbd> <kbd>P</kbd> | Put the last deleted or yanked text after / before the cursor
+//! | <kbd>i</kbd> <kbd>a</kbd> <kbd>I</kbd> <kbd>A</kbd> | Enter insert mode before / after the cursor, at the first non-blank / end of the line
+//! | <kbd>u</kbd> <kbd>Ctrl + R</kbd> | Undo / redo
+//! | <kbd>k</kbd> <kbd>j</kbd> | Recall the previous / next entry from history
+//!
+//! Motions and operators accept a count, e.g. `3w` or `2d3w`.
+
+use std::ops::Range;
+
+use crate::{
+    core::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    preset::readline::{evaluate, Readline},
+    Signal,
+};
+
+/// Vi editing mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    /// Keys edit the text as usual.
+    Insert,
+    /// Keys are commands, e.g. motions and operators.
+    Normal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Find {
+    forward: bool,
+    /// Stops right before the char (`t`/`T`) instead of on it (`f`/`F`).
+    till: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    Head,
+    FirstNonBlank,
+    Tail,
+    Find(Find, char),
+}
+
+impl Motion {
+    /// Whether the char under the target is covered by an operator.
+    fn is_inclusive(&self) -> bool {
+        matches!(
+            self,
+            Motion::WordEnd | Motion::Tail | Motion::Find(Find { forward: true, .. }, _)
+        )
+    }
+}
+
+/// State of the vi key bindings.
+#[derive(Clone)]
+pub struct Vi {
+    /// The current mode.
+    pub mode: Mode,
+    /// The prefix shown in place of the text editor prefix in normal mode,
+    /// indicating the mode.
+    pub normal_prefix: String,
+
+    /// The count typed so far.
+    count: Option<usize>,
+    /// The pending operator with the count typed before it.
+    operator: Option<(Operator, usize)>,
+    /// The pending find motion waiting for its char.
+    find: Option<Find>,
+}
+
+impl Default for Vi {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Insert,
+            normal_prefix: String::from("❮❮ "),
+            count: None,
+            operator: None,
+            find: None,
+        }
+    }
+}
+
+impl Vi {
+    /// Goes back to insert mode and discards the pending command.
+    pub fn reset(&mut self) {
+        self.mode = Mode::Insert;
+        self.reset_pending();
+    }
+
+    fn reset_pending(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.find = None;
+    }
+}
+
+/// Key bindings of the vi keymap, dispatched by the current mode.
+pub async fn readline(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal> {
+    match ctx.vi.mode {
+        Mode::Insert => insert(event, ctx).await,
+        Mode::Normal => normal(event, ctx).await,
+    }
+}
+
+async fn insert(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal> {
+    match event {
+        Event::Key(KeyEvent {
+            code: KeyCode::Esc,
+            kind: KeyEventKind::Press,
+            ..
+        }) => {
+            ctx.vi.mode = Mode::Normal;
+            ctx.vi.reset_pending();
+            // Like vi, the cursor moves onto the last inserted char.
+            ctx.readline.texteditor.backward();
+            Ok(Signal::Continue)
+        }
+        _ => evaluate::readline(event, ctx).await,
+    }
+}
+
+async fn normal(event: &Event, ctx: &mut Readline) -> anyhow::Result<Signal> {
+    let Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind: KeyEventKind::Press,
+        ..
+    }) = event
+    else {
+        return Ok(Signal::Continue);
+    };
+
+    let ch = match (code, *modifiers) {
+        (KeyCode::Char(ch), KeyModifiers::NONE | KeyModifiers::SHIFT) => *ch,
+        (KeyCode::Left, KeyModifiers::NONE) => 'h',
+        (KeyCode::Right, KeyModifiers::NONE) => 'l',
+        (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+            ctx.vi.reset_pending();
+            ctx.readline.texteditor.redo();
+            clamp_cursor(ctx);
+            return Ok(Signal::Continue);
+        }
+        // Submitting and recalling history work as in insert mode.
+        (KeyCode::Enter | KeyCode::Up | KeyCode::Down, KeyModifiers::NONE) => {
+            ctx.vi.reset_pending();
+            let ret = evaluate::readline(event, ctx).await;
+            clamp_cursor(ctx);
+            return ret;
+        }
+        _ => {
+            ctx.vi.reset_pending();
+            return Ok(Signal::Continue);
+        }
+    };
+
+    if let Some(find) = ctx.vi.find.take() {
+        run_motion(ctx, Motion::Find(find, ch));
+        return Ok(Signal::Continue);
+    }
+
+    match ch {
+        '1'..='9' => push_digit(ctx, ch),
+        '0' if ctx.vi.count.is_some() => push_digit(ctx, ch),
+
+        'h' => run_motion(ctx, Motion::Left),
+        'l' | ' ' => run_motion(ctx, Motion::Right),
+        'w' => run_motion(ctx, Motion::WordForward),
+        'b' => run_motion(ctx, Motion::WordBackward),
+        'e' => run_motion(ctx, Motion::WordEnd),
+        '0' => run_motion(ctx, Motion::Head),
+        '^' => run_motion(ctx, Motion::FirstNonBlank),
+        '$' => run_motion(ctx, Motion::Tail),
+        'f' | 't' | 'F' | 'T' => {
+            ctx.vi.find = Some(Find {
+                forward: ch.is_ascii_lowercase(),
+                till: ch.eq_ignore_ascii_case(&'t'),
+            })
+        }
+
+        'd' | 'c' | 'y' => {
+            let op = match ch {
+                'd' => Operator::Delete,
+                'c' => Operator::Change,
+                _ => Operator::Yank,
+            };
+            match ctx.vi.operator {
+                // `dd`, `cc` and `yy` apply to the whole line.
+                Some((pending, _)) if pending == op => {
+                    let line = ctx.readline.texteditor.text_without_cursor();
+                    if op == Operator::Yank {
+                        // Unlike other yanks, `yy` leaves the cursor in place.
+                        ctx.readline.kill_ring.push(line.to_string());
+                    } else {
+                        apply(ctx, op, 0..line.len());
+                    }
+                    ctx.vi.reset_pending();
+                }
+                Some(_) => ctx.vi.reset_pending(),
+                None => {
+                    let count = ctx.vi.count.take().unwrap_or(1);
+                    ctx.vi.operator = Some((op, count));
+                }
+            }
+        }
+
+        // The remaining commands are not motions, so they cancel a pending operator.
+        _ if ctx.vi.operator.is_some() => ctx.vi.reset_pending(),
+
+        'x' => with_operator(ctx, Operator::Delete, Motion::Right),
+        'X' => with_operator(ctx, Operator::Delete, Motion::Left),
+        'D' => with_operator(ctx, Operator::Delete, Motion::Tail),
+        'C' => with_operator(ctx, Operator::Change, Motion::Tail),
+        's' => with_operator(ctx, Operator::Change, Motion::Right),
+        'S' => {
+            let len = ctx.readline.texteditor.text_without_cursor().len();
+            apply(ctx, Operator::Change, 0..len);
+            ctx.vi.reset_pending();
+        }
+
+        'p' | 'P' => {
+            let count = ctx.vi.count.take().unwrap_or(1);
+            put(ctx, ch == 'p', count);
+            ctx.vi.reset_pending();
+        }
+
+        'i' => enter_insert(ctx),
+        'a' => {
+            if !ctx.readline.texteditor.text_without_cursor().is_empty() {
+                ctx.readline.texteditor.forward();
+            }
+            enter_insert(ctx);
+        }
+        'I' => {
+            let text = graphemes(ctx);
+            ctx.readline.texteditor.move_to(first_non_blank(&text));
+            enter_insert(ctx);
+        }
+        'A' => {
+            ctx.readline.texteditor.move_to_tail();
+            enter_insert(ctx);
+        }
+
+        'u' => {
+            let count = ctx.vi.count.take().unwrap_or(1);
+            for _ in 0..count {
+                ctx.readline.texteditor.undo();
+            }
+            ctx.vi.reset_pending();
+        }
+
+        'k' | 'j' => {
+            if let Some(history) = &mut ctx.readline.history {
+                let moved = if ch == 'k' {
+                    history.backward()
+                } else {
+                    history.forward()
+                };
+                if moved {
+                    ctx.readline.texteditor.replace(&history.get());
+                }
+            }
+            ctx.vi.reset_pending();
+        }
+
+        _ => ctx.vi.reset_pending(),
+    }
+
+    clamp_cursor(ctx);
+    Ok(Signal::Continue)
+}
+
+fn push_digit(ctx: &mut Readline, digit: char) {
+    let digit = digit.to_digit(10).unwrap_or_default() as usize;
+    ctx.vi.count = Some(
+        ctx.vi
+            .count
+            .unwrap_or_default()
+            .saturating_mul(10)
+            .saturating_add(digit),
+    );
+}
+
+fn enter_insert(ctx: &mut Readline) {
+    ctx.vi.reset_pending();
+    ctx.vi.mode = Mode::Insert;
+}
+
+/// Runs the motion with the current count,
+/// either moving the cursor or applying the pending operator.
+fn run_motion(ctx: &mut Readline, motion: Motion) {
+    let operator = ctx.vi.operator.take();
+    let count = ctx.vi.count.take().unwrap_or(1) * operator.map_or(1, |(_, count)| count);
+    let text = graphemes(ctx);
+    let pos = ctx.readline.texteditor.position();
+
+    match operator {
+        None => {
+            if let Some(target) = target(&text, pos, motion, count) {
+                ctx.readline.texteditor.move_to(target);
+            }
+        }
+        Some((op, _)) => {
+            let range = if op == Operator::Change
+                && motion == Motion::WordForward
+                && text.get(pos).is_some_and(|g| class(g) != Class::Blank)
+            {
+                // Like vi, `cw` on a word changes only up to its end.
+                let end = (1..count).fold(current_word_end(&text, pos), |i, _| word_end(&text, i));
+                Some(pos..end + 1)
+            } else {
+                target(&text, pos, motion, count).map(|target| {
+                    if target >= pos {
+                        let end = if motion.is_inclusive() {
+                            target + 1
+                        } else {
+                            target
+                        };
+                        pos..end
+                    } else {
+                        target..pos
+                    }
+                })
+            };
+            if let Some(range) = range {
+                apply(ctx, op, range);
+            }
+        }
+    }
+    ctx.vi.reset_pending();
+}
+
+fn with_operator(ctx: &mut Readline, op: Operator, motion: Motion) {
+    ctx.vi.operator = Some((op, 1));
+    run_motion(ctx, motion);
+}
+
+/// Applies the operator to the graphemes in `range`.
+/// Deleted and yanked text goes to the kill ring, to be put back with `p`.
+fn apply(ctx: &mut Readline, op: Operator, range: Range<usize>) {
+    match op {
+        Operator::Delete | Operator::Change => {
+            let erased = ctx.readline.texteditor.erase_range(range);
+            ctx.readline.kill_ring.push(erased);
+            if op == Operator::Change {
+                ctx.vi.mode = Mode::Insert;
+            }
+        }
+        Operator::Yank => {
+            let text = graphemes(ctx);
+            let end = range.end.min(text.len());
+            let start = range.start.min(end);
+            ctx.readline.kill_ring.push(text[start..end].concat());
+            ctx.readline.texteditor.move_to(start);
+        }
+    }
+}
+
+/// Puts the text at the top of the kill ring after or before the cursor,
+/// leaving the cursor on the last char put.
+fn put(ctx: &mut Readline, after: bool, count: usize) {
+    let Some(text) = ctx.readline.kill_ring.get().map(|text| text.repeat(count)) else {
+        return;
+    };
+    if after && !ctx.readline.texteditor.text_without_cursor().is_empty() {
+        ctx.readline.texteditor.forward();
+    }
+    ctx.readline.texteditor.insert_str(&text);
+    ctx.readline.texteditor.backward();
+}
+
+/// Keeps the cursor on a char in normal mode, as it cannot be past the end of the line.
+fn clamp_cursor(ctx: &mut Readline) {
+    if ctx.vi.mode == Mode::Normal {
+        let len = ctx.readline.texteditor.text_without_cursor().len();
+        if len > 0 && ctx.readline.texteditor.position() >= len {
+            ctx.readline.texteditor.move_to(len - 1);
+        }
+    }
+}
+
+fn graphemes(ctx: &Readline) -> Vec<String> {
+    ctx.readline
+        .texteditor
+        .text_without_cursor()
+        .iter()
+        .map(|g| g.as_str().to_string())
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Class {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn class(grapheme: &str) -> Class {
+    match grapheme.chars().next() {
+        Some(ch) if ch.is_whitespace() => Class::Blank,
+        Some(ch) if ch.is_alphanumeric() || ch == '_' => Class::Word,
+        _ => Class::Punctuation,
+    }
+}
+
+/// Returns the position the motion moves to from `pos`, repeated `count` times,
+/// or `None` if the motion fails (e.g. the char to find is missing).
+fn target<S: AsRef<str>>(text: &[S], pos: usize, motion: Motion, count: usize) -> Option<usize> {
+    let len = text.len();
+    let repeat = |f: &dyn Fn(usize) -> usize| (0..count).fold(pos, |i, _| f(i));
+    match motion {
+        Motion::Left => Some(pos.saturating_sub(count)),
+        Motion::Right => Some((pos + count).min(len)),
+        Motion::WordForward => Some(repeat(&|i| next_word_start(text, i))),
+        Motion::WordBackward => Some(repeat(&|i| previous_word_start(text, i))),
+        Motion::WordEnd => Some(repeat(&|i| word_end(text, i))),
+        Motion::Head => Some(0),
+        Motion::FirstNonBlank => Some(first_non_blank(text)),
+        Motion::Tail => Some(len.saturating_sub(1)),
+        Motion::Find(find, ch) => find_char(text, pos, find, ch, count),
+    }
+}
+
+fn next_word_start<S: AsRef<str>>(text: &[S], pos: usize) -> usize {
+    let len = text.len();
+    let mut i = pos;
+    if let Some(g) = text.get(i) {
+        let start = class(g.as_ref());
+        if start != Class::Blank {
+            while i < len && class(text[i].as_ref()) == start {
+                i += 1;
+            }
+        }
+    }
+    while i < len && class(text[i].as_ref()) == Class::Blank {
+        i += 1;
+    }
+    i.min(len)
+}
+
+fn previous_word_start<S: AsRef<str>>(text: &[S], pos: usize) -> usize {
+    let mut i = pos.min(text.len());
+    while i > 0 && class(text[i - 1].as_ref()) == Class::Blank {
+        i -= 1;
+    }
+    if let Some(start) = i.checked_sub(1).map(|j| class(text[j].as_ref())) {
+        while i > 0 && class(text[i - 1].as_ref()) == start {
+            i -= 1;
+        }
+    }
+    i
+}
+
+fn word_end<S: AsRef<str>>(text: &[S], pos: usize) -> usize {
+    let len = text.len();
+    let mut i = pos + 1;
+    while i < len && class(text[i].as_ref()) == Class::Blank {
+        i += 1;
+    }
+    if i >= len {
+        return len.saturating_sub(1);
+    }
+    current_word_end(text, i)
+}
+
+/// Returns the end of the run of chars of the same class as the one at `pos`.
+fn current_word_end<S: AsRef<str>>(text: &[S], pos: usize) -> usize {
+    let Some(start) = text.get(pos).map(|g| class(g.as_ref())) else {
+        return pos;
+    };
+    let mut i = pos;
+    while i + 1 < text.len() && class(text[i + 1].as_ref()) == start {
+        i += 1;
+    }
+    i
+}
+
+fn first_non_blank<S: AsRef<str>>(text: &[S]) -> usize {
+    text.iter()
+        .position(|g| class(g.as_ref()) != Class::Blank)
+        .unwrap_or(0)
+}
+
+/// Finds the `count`-th occurrence of `ch` from `pos` in the direction of `find`.
+/// A till motion to a `ch` next to `pos` is empty, as in Vim, and finds nothing.
+fn find_char<S: AsRef<str>>(
+    text: &[S],
+    pos: usize,
+    find: Find,
+    ch: char,
+    count: usize,
+) -> Option<usize> {
+    let mut buf = [0; 4];
+    let needle: &str = ch.encode_utf8(&mut buf);
+    let is_match = |i: &usize| text[*i].as_ref() == needle;
+    let nth = count.saturating_sub(1);
+    if find.forward {
+        let found = (pos + 1..text.len()).filter(is_match).nth(nth)?;
+        match find.till {
+            true if found - 1 == pos => None,
+            true => Some(found - 1),
+            false => Some(found),
+        }
+    } else {
+        let found = (0..pos.min(text.len())).rev().filter(is_match).nth(nth)?;
+        match find.till {
+            true if found + 1 == pos => None,
+            true => Some(found + 1),
+            false => Some(found),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{core::backend::Memory, preset::readline::Keymap, test_util::key, Prompt};
+
+    fn chars(s: &str) -> Vec<String> {
+        s.chars().map(String::from).collect()
+    }
+
+    mod target {
+        use super::*;
+
+        #[test]
+        fn test_word_motions() {
+            let text = chars("foo.bar  baz");
+            assert_eq!(Some(3), target(&text, 0, Motion::WordForward, 1));
+            assert_eq!(Some(9), target(&text, 0, Motion::WordForward, 3));
+            assert_eq!(Some(12), target(&text, 9, Motion::WordForward, 1));
+            assert_eq!(Some(4), target(&text, 9, Motion::WordBackward, 1));
+            assert_eq!(Some(0), target(&text, 2, Motion::WordBackward, 1));
+            assert_eq!(Some(2), target(&text, 0, Motion::WordEnd, 1));
+            assert_eq!(Some(6), target(&text, 2, Motion::WordEnd, 2));
+            assert_eq!(Some(11), target(&text, 6, Motion::WordEnd, 1));
+        }
+
+        #[test]
+        fn test_line_motions() {
+            let text = chars("  abc");
+            assert_eq!(Some(0), target(&text, 3, Motion::Head, 1));
+            assert_eq!(Some(2), target(&text, 4, Motion::FirstNonBlank, 1));
+            assert_eq!(Some(4), target(&text, 0, Motion::Tail, 1));
+        }
+
+        #[test]
+        fn test_find() {
+            let text = chars("a,b,c");
+            let f = Find {
+                forward: true,
+                till: false,
+            };
+            let t = Find {
+                forward: true,
+                till: true,
+            };
+            let big_f = Find {
+                forward: false,
+                till: false,
+            };
+            let big_t = Find {
+                forward: false,
+                till: true,
+            };
+            assert_eq!(Some(1), target(&text, 0, Motion::Find(f, ','), 1));
+            assert_eq!(Some(3), target(&text, 0, Motion::Find(f, ','), 2));
+            assert_eq!(None, target(&text, 0, Motion::Find(f, ','), 3));
+            assert_eq!(None, target(&text, 0, Motion::Find(t, ','), 1));
+            assert_eq!(Some(2), target(&text, 0, Motion::Find(t, ','), 2));
+            assert_eq!(Some(3), target(&text, 4, Motion::Find(big_f, ','), 1));
+            assert_eq!(None, target(&text, 4, Motion::Find(big_t, ','), 1));
+            assert_eq!(Some(2), target(&text, 4, Motion::Find(big_t, ','), 2));
+        }
+    }
+
+    mod readline {
+        use super::*;
+
+        fn keys(s: &str) -> Vec<Event> {
+            s.chars()
+                .map(|ch| {
+                    let code = match ch {
+                        '\x1b' => KeyCode::Esc,
+                        '\n' => KeyCode::Enter,
+                        ch => KeyCode::Char(ch),
+                    };
+                    key(code)
+                })
+                .collect()
+        }
+
+        async fn run(s: &str) -> String {
+            Readline::default()
+                .keymap(Keymap::Vi)
+                .backend(Memory::new(40, 5))
+                .run_with_events(keys(s))
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_operators() {
+            assert_eq!("world", run("hello world\x1b0dw\n").await);
+            assert_eq!("c d", run("a b c d\x1b02dw\n").await);
+            assert_eq!("a ", run("a b c d\x1b0wd2w2x\n").await);
+            assert_eq!("=value", run("key=value\x1b0dt=\n").await);
+            // Till a char next to the cursor is an empty motion.
+            assert_eq!("a,b", run("a,b\x1b0dt,\n").await);
+            assert_eq!("a,b", run("a,b\x1bdT,\n").await);
+            assert_eq!("qux bar", run("foo bar\x1b0cwqux\x1b\n").await);
+            assert_eq!("new", run("old\x1bccnew\n").await);
+        }
+
+        #[tokio::test]
+        async fn test_put_and_undo() {
+            assert_eq!("abab", run("ab\x1byyp\n").await);
+            assert_eq!("ba", run("ab\x1b0xp\n").await);
+            assert_eq!("abc", run("abc\x1bDu\n").await);
+        }
+
+        #[tokio::test]
+        async fn test_insert_commands() {
+            assert_eq!("xaby", run("ab\x1bIx\x1bAy\n").await);
+            assert_eq!("a-b", run("ab\x1b0a-\n").await);
+        }
+
+        #[tokio::test]
+        async fn test_mode_indicator() {
+            let memory = Memory::new(20, 5);
+            let mut readline = Readline::default()
+                .keymap(Keymap::Vi)
+                .backend(memory.clone());
+
+            readline.run_with_events(keys("ab")).await.unwrap();
+            assert_eq!("❯❯ ab", memory.lines()[0]);
+
+            let memory = Memory::new(20, 5);
+            let mut readline = Readline::default()
+                .keymap(Keymap::Vi)
+                .backend(memory.clone());
+
+            readline.run_with_events(keys("ab\x1b")).await.unwrap();
+            assert_eq!("❮❮ ab", memory.lines()[0]);
+        }
+    }
+}
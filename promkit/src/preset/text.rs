@@ -2,11 +2,12 @@
 
 use crate::{
     core::{
-        crossterm::{self, event::Event, style::ContentStyle},
+        backend::{Backend, Stdout},
+        crossterm::{event::Event, style::ContentStyle},
         render::{Renderer, SharedRenderer},
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::text::{self, config::Config},
     Signal,
 };
@@ -25,6 +26,8 @@ pub struct Text {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// Text state containing the text to be displayed.
     pub text: text::State,
 }
@@ -32,9 +35,10 @@ pub struct Text {
 #[async_trait::async_trait]
 impl crate::Prompt for Text {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_backend_and_graphemes(
+                self.backend.clone(),
                 [(Index::Text, self.text.create_graphemes(size.0, size.1))],
                 true,
             )
@@ -44,8 +48,8 @@ impl crate::Prompt for Text {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -55,6 +59,10 @@ impl crate::Prompt for Text {
     fn finalize(&mut self) -> anyhow::Result<Self::Return> {
         Ok(())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Text {
@@ -62,7 +70,8 @@ impl Text {
     pub fn new<T: AsRef<str>>(text: T) -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             text: text::State {
                 text: text::Text::from(text),
                 config: Config::default(),
@@ -77,8 +86,17 @@ impl Text {
     }
 
     /// Sets the evaluator function for the text prompt.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
         self
     }
 
@@ -2,15 +2,16 @@
 
 use crate::{
     core::{
+        backend::{Backend, Stdout},
         crossterm::{
-            self,
             event::Event,
             style::{Attribute, Attributes, Color, ContentStyle},
         },
         render::{Renderer, SharedRenderer},
+        terminal::PaneConstraint,
         Widget,
     },
-    preset::Evaluator,
+    preset::{self, EvaluateFuture, Evaluator},
     widgets::{
         text::{self, Text},
         tree::{self, config::Config, node::Node},
@@ -34,6 +35,8 @@ pub struct Tree {
     pub renderer: Option<SharedRenderer<Index>>,
     /// Function to evaluate the input events and update the state of the prompt.
     pub evaluator: Evaluator<Self>,
+    /// Backend the prompt is drawn on.
+    pub backend: Box<dyn Backend>,
     /// State for the title displayed above the tree.
     pub title: text::State,
     /// State for the tree itself.
@@ -43,13 +46,15 @@ pub struct Tree {
 #[async_trait::async_trait]
 impl crate::Prompt for Tree {
     async fn initialize(&mut self) -> anyhow::Result<()> {
-        let size = crossterm::terminal::size()?;
+        let size = self.backend.size()?;
         self.renderer = Some(SharedRenderer::new(
-            Renderer::try_new_with_graphemes(
+            Renderer::try_new_with_constraints(
+                self.backend.clone(),
                 [
                     (Index::Title, self.title.create_graphemes(size.0, size.1)),
                     (Index::Tree, self.tree.create_graphemes(size.0, size.1)),
                 ],
+                [(Index::Tree, PaneConstraint::default().priority(1))],
                 true,
             )
             .await?,
@@ -58,8 +63,8 @@ impl crate::Prompt for Tree {
     }
 
     async fn evaluate(&mut self, event: &Event) -> anyhow::Result<Signal> {
-        let ret = (self.evaluator)(event, self).await;
-        let size = crossterm::terminal::size()?;
+        let ret = preset::evaluate(self, |ctx| &mut ctx.evaluator, event).await;
+        let size = self.backend.size()?;
         self.render(size.0, size.1).await?;
         ret
     }
@@ -69,6 +74,10 @@ impl crate::Prompt for Tree {
     fn finalize(&mut self) -> anyhow::Result<Self::Return> {
         Ok(self.tree.tree.get())
     }
+
+    fn output_backend(&self) -> Box<dyn Backend> {
+        self.backend.clone()
+    }
 }
 
 impl Tree {
@@ -76,7 +85,8 @@ impl Tree {
     pub fn new(root: Node) -> Self {
         Self {
             renderer: None,
-            evaluator: |event, ctx| Box::pin(evaluate::default(event, ctx)),
+            evaluator: Box::new(|event, ctx| Box::pin(evaluate::default(event, ctx))),
+            backend: Box::new(Stdout),
             title: text::State {
                 config: text::config::Config {
                     style: Some(ContentStyle {
@@ -153,8 +163,17 @@ impl Tree {
     }
 
     /// Sets the evaluator function for processing events in the tree.
-    pub fn evaluator(mut self, evaluator: Evaluator<Self>) -> Self {
-        self.evaluator = evaluator;
+    pub fn evaluator<F>(mut self, evaluator: F) -> Self
+    where
+        F: for<'a> FnMut(&'a Event, &'a mut Self) -> EvaluateFuture<'a> + Send + 'static,
+    {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    /// Sets the backend to draw the prompt on, e.g. `Stderr` to keep stdout free for piping.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Box::new(backend);
         self
     }
 
@@ -2,6 +2,11 @@ use std::{fmt, iter::FromIterator};
 
 use radix_trie::{Trie, TrieCommon};
 
+mod completer;
+pub use completer::{Candidate, Completer, Context};
+mod path;
+pub use path::PathCompleter;
+
 /// A structure to store and manage suggestions for autocompletion.
 /// It utilizes a trie for efficient storage and retrieval of suggestions.
 /// This allows for quick lookup of suggestions based on a given prefix,
@@ -33,6 +38,19 @@ impl Suggest {
     }
 }
 
+#[async_trait::async_trait]
+impl Completer for Suggest {
+    /// Returns the items starting with the input up to the cursor.
+    async fn complete(&self, context: &Context<'_>) -> anyhow::Result<Vec<Candidate>> {
+        Ok(self
+            .prefix_search(context.query())
+            .unwrap_or_default()
+            .into_iter()
+            .map(Candidate::from)
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,4 +76,38 @@ mod tests {
             assert_eq!(ret, expected);
         }
     }
+
+    mod complete {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let suggest = Suggest::from_iter(["main", "feature-a", "feature-b"]);
+            let context = Context {
+                line: "git checkout fea -b",
+                position: 16,
+                start: 13,
+            };
+            let ret = suggest.complete(&context).await.unwrap();
+            assert_eq!(
+                vec![Candidate::new("feature-a"), Candidate::new("feature-b")],
+                ret
+            );
+        }
+
+        #[tokio::test]
+        async fn test_with_closure() {
+            let completer = |context: &Context<'_>| {
+                vec![Candidate::new(format!("{}!", context.query())).description("shout")]
+            };
+            let context = Context {
+                line: "hi",
+                position: 2,
+                start: 0,
+            };
+            let ret = completer.complete(&context).await.unwrap();
+            assert_eq!(Some("shout"), ret[0].description.as_deref());
+            assert_eq!("hi!", ret[0].value);
+        }
+    }
 }
@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// A completion candidate, optionally with a description shown next to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    /// The text that replaces the completed part of the input.
+    pub value: String,
+    /// Optional description, e.g. the last commit of a branch.
+    pub description: Option<String>,
+}
+
+impl Candidate {
+    /// Creates a candidate without a description.
+    pub fn new<T: fmt::Display>(value: T) -> Self {
+        Self {
+            value: value.to_string(),
+            description: None,
+        }
+    }
+
+    /// Sets the description shown next to the candidate.
+    pub fn description<T: fmt::Display>(mut self, description: T) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+impl From<String> for Candidate {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for Candidate {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// The input to complete, passed to [`Completer::complete`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Context<'a> {
+    /// The whole input.
+    pub line: &'a str,
+    /// The cursor position in `line` as a byte offset,
+    /// or the end of `line` when the whole line is completed.
+    pub position: usize,
+    /// The byte offset in `line` where the completed part starts,
+    /// i.e. `0` for the whole line or the start of the word at the cursor.
+    /// The candidates replace the text from here to the end of the word or the line.
+    pub start: usize,
+}
+
+impl<'a> Context<'a> {
+    /// Returns the completed part of the input up to the cursor.
+    pub fn query(&self) -> &'a str {
+        &self.line[self.start..self.position]
+    }
+}
+
+/// A source of completion candidates, e.g. a fixed list, git branches or file paths.
+///
+/// Candidates are requested lazily when completion is triggered,
+/// so implementations may run commands or read the file system.
+/// Synchronous closures of `Fn(&Context) -> Vec<Candidate>` implement this trait as well.
+#[async_trait::async_trait]
+pub trait Completer: Send + Sync {
+    /// Returns the candidates for the given input.
+    async fn complete(&self, context: &Context<'_>) -> anyhow::Result<Vec<Candidate>>;
+
+    /// Whether a word break character escaped with a backslash, as in `my\ dir`,
+    /// belongs to the completed word. Defaults to `false`.
+    fn escapes_word_breaks(&self) -> bool {
+        false
+    }
+}
+
+#[async_trait::async_trait]
+impl<F> Completer for F
+where
+    F: Fn(&Context<'_>) -> Vec<Candidate> + Send + Sync,
+{
+    async fn complete(&self, context: &Context<'_>) -> anyhow::Result<Vec<Candidate>> {
+        Ok(self(context))
+    }
+}
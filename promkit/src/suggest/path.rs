@@ -0,0 +1,226 @@
+use std::path::{Path, PathBuf};
+
+use super::{Candidate, Completer, Context};
+
+/// Completes file and directory paths, e.g. for prompts asking for a path.
+///
+/// The typed prefix is completed relative to the current directory
+/// (or `base`), with `~` expanded to the home directory.
+/// Directories are completed with a trailing `/`.
+/// Pass it to `Readline::completer` to complete the input on <kbd>Tab</kbd>.
+/// When completing a word of a command line with `CompletionScope::Word`,
+/// enable `escape` so that the names with spaces stay one word.
+#[derive(Clone, Debug, Default)]
+pub struct PathCompleter {
+    /// Whether to list hidden files even if the typed name does not start with `.`.
+    pub show_hidden: bool,
+    /// Whether to list only directories.
+    pub directories_only: bool,
+    /// Directory that relative paths are resolved from, instead of the current directory.
+    pub base: Option<PathBuf>,
+    /// Whether spaces and backslashes are escaped with a backslash,
+    /// in the candidates as well as in the typed path.
+    pub escape: bool,
+}
+
+impl PathCompleter {
+    /// Sets whether to list hidden files even if the typed name does not start with `.`.
+    pub fn show_hidden(mut self, enabled: bool) -> Self {
+        self.show_hidden = enabled;
+        self
+    }
+
+    /// Sets whether to list only directories.
+    pub fn directories_only(mut self, enabled: bool) -> Self {
+        self.directories_only = enabled;
+        self
+    }
+
+    /// Sets the directory that relative paths are resolved from.
+    pub fn base<P: AsRef<Path>>(mut self, base: P) -> Self {
+        self.base = Some(base.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets whether spaces and backslashes are escaped with a backslash.
+    pub fn escape(mut self, enabled: bool) -> Self {
+        self.escape = enabled;
+        self
+    }
+}
+
+/// Escapes spaces and backslashes with a backslash.
+fn escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if matches!(ch, ' ' | '\\') {
+            ret.push('\\');
+        }
+        ret.push(ch);
+    }
+    ret
+}
+
+/// Reverts `escape`.
+fn unescape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => ret.push(chars.next().unwrap_or('\\')),
+            ch => ret.push(ch),
+        }
+    }
+    ret
+}
+
+/// Replaces a leading `~` with the home directory, if known.
+fn expand_tilde(path: &str, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+#[async_trait::async_trait]
+impl Completer for PathCompleter {
+    /// Returns the entries of the typed directory starting with the typed name.
+    /// A directory that cannot be read yields no candidates.
+    async fn complete(&self, context: &Context<'_>) -> anyhow::Result<Vec<Candidate>> {
+        let query = context.query();
+        // `~` alone is completed as the home directory itself.
+        let query = if query == "~" { "~/" } else { query };
+        let (typed_dir, name) = match query.rfind('/') {
+            Some(i) => query.split_at(i + 1),
+            None => ("", query),
+        };
+        let (name, dir) = if self.escape {
+            (unescape(name), unescape(typed_dir))
+        } else {
+            (name.to_string(), typed_dir.to_string())
+        };
+
+        let dir = expand_tilde(&dir, home_dir().as_deref());
+        let dir = match (&self.base, dir.is_relative()) {
+            (Some(base), true) => base.join(dir),
+            (None, true) if typed_dir.is_empty() => PathBuf::from("."),
+            _ => dir,
+        };
+
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            return Ok(Vec::new());
+        };
+        let mut ret = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if !file_name.starts_with(&name)
+                || (file_name.starts_with('.') && !self.show_hidden && !name.starts_with('.'))
+            {
+                continue;
+            }
+            // Follow symbolic links to tell whether the target is a directory.
+            let is_dir = tokio::fs::metadata(entry.path())
+                .await
+                .is_ok_and(|metadata| metadata.is_dir());
+            if self.directories_only && !is_dir {
+                continue;
+            }
+            ret.push(format!(
+                "{typed_dir}{}{}",
+                if self.escape {
+                    escape(&file_name)
+                } else {
+                    file_name
+                },
+                if is_dir { "/" } else { "" }
+            ));
+        }
+        ret.sort();
+        Ok(ret.into_iter().map(Candidate::from).collect())
+    }
+
+    /// Escaped spaces belong to the typed path when `escape` is set.
+    fn escapes_word_breaks(&self) -> bool {
+        self.escape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(line: &str) -> Context<'_> {
+        Context {
+            line,
+            position: line.len(),
+            start: 0,
+        }
+    }
+
+    async fn values(completer: &PathCompleter, line: &str) -> Vec<String> {
+        completer
+            .complete(&context(line))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|candidate| candidate.value)
+            .collect()
+    }
+
+    mod complete {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let base = std::env::temp_dir().join(format!("promkit-path-{}", std::process::id()));
+            std::fs::create_dir_all(base.join("alpha")).unwrap();
+            std::fs::create_dir_all(base.join("my dir")).unwrap();
+            std::fs::write(base.join("alpha/beta"), "").unwrap();
+            std::fs::write(base.join("alpine.txt"), "").unwrap();
+            std::fs::write(base.join(".hidden"), "").unwrap();
+
+            let completer = PathCompleter::default().base(&base);
+            assert_eq!(vec!["alpha/", "alpine.txt"], values(&completer, "al").await);
+            assert_eq!(
+                vec!["alpha/", "alpine.txt", "my dir/"],
+                values(&completer, "").await
+            );
+            assert_eq!(vec![".hidden"], values(&completer, ".").await);
+            assert_eq!(vec!["my dir/"], values(&completer, "my d").await);
+            assert_eq!(vec!["alpha/beta"], values(&completer, "alpha/").await);
+            assert!(values(&completer, "missing/").await.is_empty());
+
+            let completer = completer.show_hidden(true).directories_only(true);
+            assert_eq!(vec!["alpha/", "my dir/"], values(&completer, "").await);
+
+            let completer = completer.escape(true);
+            assert_eq!(vec!["my\\ dir/"], values(&completer, "my\\ d").await);
+
+            std::fs::remove_dir_all(base).unwrap();
+        }
+    }
+
+    mod expand_tilde {
+        use super::*;
+
+        #[test]
+        fn test() {
+            let home = Path::new("/home/user");
+            assert_eq!(
+                PathBuf::from("/home/user/docs"),
+                expand_tilde("~/docs", Some(home))
+            );
+            assert_eq!(PathBuf::from("/home/user"), expand_tilde("~", Some(home)));
+            assert_eq!(PathBuf::from("~other"), expand_tilde("~other", Some(home)));
+            assert_eq!(PathBuf::from("~/docs"), expand_tilde("~/docs", None));
+        }
+    }
+}
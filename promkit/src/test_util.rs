@@ -0,0 +1,26 @@
+//! Helpers for scripting the events given to prompts in tests.
+
+// Unused when the presets using them are disabled by features.
+#![allow(dead_code)]
+
+use crate::core::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+/// Creates a key press event without modifiers.
+pub fn key(code: KeyCode) -> Event {
+    key_with(code, KeyModifiers::NONE)
+}
+
+/// Creates a key press event with the modifiers held.
+pub fn key_with(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+/// Creates the key press events typing the string, a newline being Enter.
+pub fn type_str(s: &str) -> Vec<Event> {
+    s.chars()
+        .map(|ch| match ch {
+            '\n' => key(KeyCode::Enter),
+            ch => key(KeyCode::Char(ch)),
+        })
+        .collect()
+}
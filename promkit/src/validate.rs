@@ -1,5 +1,19 @@
-pub type Validator<T> = fn(&T) -> bool;
-pub type ErrorMessageGenerator<T> = fn(&T) -> String;
+use std::{future::Future, pin::Pin};
+
+mod rule;
+pub use rule::Rule;
+
+/// A function that checks whether the input is valid.
+/// Closures can capture their environment (e.g. a list of existing names).
+pub type Validator<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+/// A function that describes why the input is invalid.
+pub type ErrorMessageGenerator<T> = Box<dyn Fn(&T) -> String + Send + Sync>;
+
+/// Type alias for the future returned by an asynchronous validator.
+pub type ValidateFuture<'a> = Pin<Box<dyn Future<Output = bool> + Send + 'a>>;
+/// A function that checks whether the input is valid asynchronously,
+/// e.g. by querying a local service or the file system.
+pub type AsyncValidator<T> = Box<dyn for<'a> Fn(&'a T) -> ValidateFuture<'a> + Send + Sync>;
 
 /// A generic structure for validating inputs of any type.
 ///
@@ -34,10 +48,14 @@ impl<T: ?Sized> ValidatorManager<T> {
     /// # Returns
     ///
     /// Returns a new instance of `Validator<T>`.
-    pub fn new(validator: Validator<T>, error_message_generator: ErrorMessageGenerator<T>) -> Self {
+    pub fn new<V, E>(validator: V, error_message_generator: E) -> Self
+    where
+        V: Fn(&T) -> bool + Send + Sync + 'static,
+        E: Fn(&T) -> String + Send + Sync + 'static,
+    {
         Self {
-            validator,
-            error_message_generator,
+            validator: Box::new(validator),
+            error_message_generator: Box::new(error_message_generator),
         }
     }
 
@@ -72,3 +90,88 @@ impl<T: ?Sized> ValidatorManager<T> {
         (self.error_message_generator)(input)
     }
 }
+
+/// An asynchronous counterpart of [`ValidatorManager`].
+///
+/// The validator returns a future, so that validation can wait for I/O
+/// while the prompt shows a pending state.
+pub struct AsyncValidatorManager<T: ?Sized> {
+    /// A function that takes a reference
+    /// to an input of type `T` and resolves to a boolean
+    /// indicating whether the input passes the validation.
+    validator: AsyncValidator<T>,
+    /// A function that takes a reference
+    /// to an input of type `T` and returns a `String`
+    /// that describes the validation error.
+    error_message_generator: ErrorMessageGenerator<T>,
+}
+
+impl<T: ?Sized> AsyncValidatorManager<T> {
+    /// Constructs a new `AsyncValidatorManager` instance
+    /// with the specified asynchronous validator and error message generator functions.
+    pub fn new<V, E>(validator: V, error_message_generator: E) -> Self
+    where
+        V: for<'a> Fn(&'a T) -> ValidateFuture<'a> + Send + Sync + 'static,
+        E: Fn(&T) -> String + Send + Sync + 'static,
+    {
+        Self {
+            validator: Box::new(validator),
+            error_message_generator: Box::new(error_message_generator),
+        }
+    }
+
+    /// Validates the given input
+    /// using the encapsulated asynchronous validator function.
+    pub async fn validate(&self, input: &T) -> bool {
+        (self.validator)(input).await
+    }
+
+    /// Generates an error message for the given input
+    /// using the encapsulated error message generator function.
+    pub fn generate_error_message(&self, input: &T) -> String {
+        (self.error_message_generator)(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod validator_manager {
+        use super::*;
+
+        #[test]
+        fn test_with_captured_state() {
+            let taken = [String::from("alice"), String::from("bob")];
+            let validator = ValidatorManager::<str>::new(
+                move |text| !taken.iter().any(|name| name == text),
+                |text| format!("{text} is already taken"),
+            );
+            assert!(validator.validate("carol"));
+            assert!(!validator.validate("bob"));
+            assert_eq!(
+                "bob is already taken",
+                validator.generate_error_message("bob")
+            );
+        }
+    }
+
+    mod async_validator_manager {
+        use super::*;
+
+        #[tokio::test]
+        async fn test() {
+            let validator = AsyncValidatorManager::<str>::new(
+                |text| {
+                    Box::pin(async move {
+                        tokio::task::yield_now().await;
+                        text.len() > 2
+                    })
+                },
+                |_| String::from("too short"),
+            );
+            assert!(validator.validate("abc").await);
+            assert!(!validator.validate("ab").await);
+        }
+    }
+}
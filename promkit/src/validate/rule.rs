@@ -0,0 +1,310 @@
+use std::{
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, LazyLock},
+};
+
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::ValidatorManager;
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[^\s@]+@[^\s@.]+(\.[^\s@.]+)+$").unwrap());
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*://[^\s/?#]+[^\s]*$").unwrap());
+
+/// A function that checks the input and returns the error message on failure.
+type Check = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// A composable validation rule for text input with its own error message.
+///
+/// Rules can be combined with [`Rule::and`], [`Rule::or`] and [`Rule::not`],
+/// and converted into a [`ValidatorManager`] to be used with
+/// `Readline`, `Password` and `Form`.
+///
+/// ```
+/// use promkit::validate::Rule;
+///
+/// let rule = Rule::non_empty().and(Rule::max_len(8));
+/// assert!(rule.check("promkit").is_ok());
+/// assert!(rule.check("").is_err());
+/// ```
+#[derive(Clone)]
+pub struct Rule {
+    check: Check,
+}
+
+impl Rule {
+    /// Creates a rule from a predicate and the error message shown when it fails.
+    pub fn new<F, S>(predicate: F, message: S) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+        S: Into<String>,
+    {
+        let message = message.into();
+        Self {
+            check: Arc::new(move |input| {
+                if predicate(input) {
+                    Ok(())
+                } else {
+                    Err(message.clone())
+                }
+            }),
+        }
+    }
+
+    /// Requires the input not to be empty.
+    pub fn non_empty() -> Self {
+        Self::new(|input| !input.is_empty(), "Input must not be empty")
+    }
+
+    /// Requires the input to have at least `min` characters,
+    /// counted as grapheme clusters like the text editor does.
+    pub fn min_len(min: usize) -> Self {
+        Self::new(
+            move |input| input.graphemes(true).count() >= min,
+            format!("Input must be at least {min} characters"),
+        )
+    }
+
+    /// Requires the input to have at most `max` characters,
+    /// counted as grapheme clusters like the text editor does.
+    pub fn max_len(max: usize) -> Self {
+        Self::new(
+            move |input| input.graphemes(true).count() <= max,
+            format!("Input must be at most {max} characters"),
+        )
+    }
+
+    /// Requires the input to match the regular expression.
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    pub fn regex(pattern: &str) -> anyhow::Result<Self> {
+        let re = Regex::new(pattern)?;
+        Ok(Self::new(
+            move |input| re.is_match(input),
+            format!("Input must match {pattern}"),
+        ))
+    }
+
+    /// Requires the input to parse as a number within `min..=max`.
+    pub fn range<N>(min: N, max: N) -> Self
+    where
+        N: FromStr + PartialOrd + Display + Send + Sync + 'static,
+    {
+        let message = format!("Input must be a number between {min} and {max}");
+        Self::new(
+            move |input| {
+                input
+                    .trim()
+                    .parse::<N>()
+                    .is_ok_and(|n| min <= n && n <= max)
+            },
+            message,
+        )
+    }
+
+    /// Requires the input to be one of the given options.
+    pub fn one_of<I, S>(options: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let options = options.into_iter().map(Into::into).collect::<Vec<String>>();
+        let message = format!("Input must be one of: {}", options.join(", "));
+        Self::new(
+            move |input| options.iter().any(|option| option == input),
+            message,
+        )
+    }
+
+    /// Requires the input to be a path that exists.
+    pub fn path_exists() -> Self {
+        Self::new(|input| Path::new(input).exists(), "Path does not exist")
+    }
+
+    /// Requires the input to look like an email address (`local@domain.tld`).
+    pub fn email() -> Self {
+        Self::new(
+            |input| EMAIL.is_match(input),
+            "Input must be a valid email address",
+        )
+    }
+
+    /// Requires the input to look like a URL (`scheme://host...`).
+    pub fn url() -> Self {
+        Self::new(|input| URL.is_match(input), "Input must be a valid URL")
+    }
+
+    /// Replaces the error message of this rule.
+    pub fn message<S: Into<String>>(self, message: S) -> Self {
+        let message = message.into();
+        Self {
+            check: Arc::new(move |input| (self.check)(input).map_err(|_| message.clone())),
+        }
+    }
+
+    /// Requires both rules to pass, reporting the first failure.
+    pub fn and(self, other: Rule) -> Self {
+        Self {
+            check: Arc::new(move |input| {
+                (self.check)(input)?;
+                (other.check)(input)
+            }),
+        }
+    }
+
+    /// Requires either rule to pass, reporting the failure of `other` when both fail.
+    pub fn or(self, other: Rule) -> Self {
+        Self {
+            check: Arc::new(move |input| (self.check)(input).or_else(|_| (other.check)(input))),
+        }
+    }
+
+    /// Inverts this rule with the error message shown when the input matches it.
+    pub fn not<S: Into<String>>(self, message: S) -> Self {
+        let message = message.into();
+        Self {
+            check: Arc::new(move |input| match (self.check)(input) {
+                Ok(()) => Err(message.clone()),
+                Err(_) => Ok(()),
+            }),
+        }
+    }
+
+    /// Checks the input, returning the error message on failure.
+    pub fn check(&self, input: &str) -> Result<(), String> {
+        (self.check)(input)
+    }
+}
+
+impl From<Rule> for ValidatorManager<str> {
+    fn from(rule: Rule) -> Self {
+        let message_rule = rule.clone();
+        ValidatorManager::new(
+            move |input: &str| rule.check(input).is_ok(),
+            move |input: &str| message_rule.check(input).err().unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod builtin {
+        use super::*;
+
+        #[test]
+        fn test_len() {
+            assert!(Rule::non_empty().check("").is_err());
+            assert!(Rule::min_len(2).check("あい").is_ok());
+            assert!(Rule::min_len(3).check("あい").is_err());
+            assert!(Rule::max_len(2).check("abc").is_err());
+        }
+
+        #[test]
+        fn test_len_with_combining_mark() {
+            // `e` followed by a combining acute accent, shown as one character.
+            assert!(Rule::max_len(1).check("e\u{301}").is_ok());
+            assert!(Rule::min_len(2).check("e\u{301}").is_err());
+        }
+
+        #[test]
+        fn test_regex() {
+            let rule = Rule::regex(r"^\d{3}$").unwrap();
+            assert!(rule.check("123").is_ok());
+            assert_eq!(
+                Err(String::from(r"Input must match ^\d{3}$")),
+                rule.check("12a")
+            );
+            assert!(Rule::regex("(").is_err());
+        }
+
+        #[test]
+        fn test_range() {
+            let rule = Rule::range(1, 10);
+            assert!(rule.check("10").is_ok());
+            assert!(rule.check("11").is_err());
+            assert!(rule.check("ten").is_err());
+            assert!(Rule::range(0.5, 1.5).check("1.0").is_ok());
+        }
+
+        #[test]
+        fn test_one_of() {
+            let rule = Rule::one_of(["y", "n"]);
+            assert!(rule.check("y").is_ok());
+            assert_eq!(
+                Err(String::from("Input must be one of: y, n")),
+                rule.check("x")
+            );
+        }
+
+        #[test]
+        fn test_path_exists() {
+            assert!(Rule::path_exists()
+                .check(env!("CARGO_MANIFEST_DIR"))
+                .is_ok());
+            assert!(Rule::path_exists().check("/path/to/nowhere").is_err());
+        }
+
+        #[test]
+        fn test_email_and_url() {
+            assert!(Rule::email().check("user@example.com").is_ok());
+            assert!(Rule::email().check("user@example").is_err());
+            assert!(Rule::email().check("a b@example.com").is_err());
+            assert!(Rule::url().check("https://example.com/path?q=1").is_ok());
+            assert!(Rule::url().check("example.com").is_err());
+        }
+    }
+
+    mod combinator {
+        use super::*;
+
+        #[test]
+        fn test_and() {
+            let rule = Rule::non_empty().and(Rule::max_len(3));
+            assert_eq!(Err(String::from("Input must not be empty")), rule.check(""));
+            assert_eq!(
+                Err(String::from("Input must be at most 3 characters")),
+                rule.check("abcd")
+            );
+            assert!(rule.check("abc").is_ok());
+        }
+
+        #[test]
+        fn test_or() {
+            let rule = Rule::email().or(Rule::url());
+            assert!(rule.check("user@example.com").is_ok());
+            assert!(rule.check("https://example.com").is_ok());
+            assert_eq!(
+                Err(String::from("Input must be a valid URL")),
+                rule.check("x")
+            );
+        }
+
+        #[test]
+        fn test_not_and_message() {
+            let rule = Rule::one_of(["root", "admin"]).not("Reserved name");
+            assert_eq!(Err(String::from("Reserved name")), rule.check("root"));
+            assert!(rule.check("alice").is_ok());
+
+            let rule = Rule::non_empty().message("Name is required");
+            assert_eq!(Err(String::from("Name is required")), rule.check(""));
+        }
+
+        #[test]
+        fn test_into_validator_manager() {
+            let validator = ValidatorManager::from(Rule::min_len(2));
+            assert!(validator.validate("ab"));
+            assert!(!validator.validate("a"));
+            assert_eq!(
+                "Input must be at least 2 characters",
+                validator.generate_error_message("a")
+            );
+        }
+    }
+}